@@ -18,7 +18,7 @@ impl Plugin for CamPlugin {
         app.add_systems(PreStartup, setup_main_camera)
             .add_systems(
                 OnTransition {
-                    exited: AppState::MainMenu,
+                    exited: AppState::NewGame,
                     entered: AppState::InGame,
                 },
                 setup_world_camera,
@@ -1,13 +1,14 @@
-use std::sync::Arc;
+use std::{fmt, str::FromStr, sync::Arc};
 
 use bevy::ecs::resource::Resource;
 use serde::{Deserialize, Serialize};
 
 use super::species::Attribute;
-use super::{element::Element, species::Species};
+use super::{element::Element, ids::AttackId, species::Species};
 
 /// Target of an attack or item usage.
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(try_from = "String")]
 pub enum Target {
     /// One enemy only
     Enemy,
@@ -25,9 +26,11 @@ pub enum Target {
     AllButSelf,
 }
 
-impl From<&str> for Target {
-    fn from(value: &str) -> Self {
-        match value.to_ascii_lowercase().as_str() {
+impl FromStr for Target {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, String> {
+        Ok(match value.to_ascii_lowercase().as_str() {
             "enemy" => Self::Enemy,
             "enemies" => Self::Enemies,
             "ally" => Self::Ally,
@@ -35,22 +38,148 @@ impl From<&str> for Target {
             "all" => Self::All,
             "self" | "oneself" => Self::OneSelf,
             "allbutself" | "abs" => Self::AllButSelf,
-            x => panic!("unknown attribute {x}"),
-        }
+            _ => return Err(format!("unknown target {value}")),
+        })
+    }
+}
+
+impl TryFrom<String> for Target {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, String> {
+        value.parse()
+    }
+}
+
+/// Stats that can be altered during a fight.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[serde(try_from = "String")]
+pub enum Stat {
+    Attack,
+    Defense,
+    Speed,
+    Dodge,
+    Accuracy,
+}
+
+impl FromStr for Stat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, String> {
+        Ok(match value.to_ascii_lowercase().as_str() {
+            "attack" => Self::Attack,
+            "defense" => Self::Defense,
+            "speed" => Self::Speed,
+            "dodge" => Self::Dodge,
+            "accuracy" => Self::Accuracy,
+            _ => return Err(format!("unknown stat {value}")),
+        })
+    }
+}
+
+impl TryFrom<String> for Stat {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, String> {
+        value.parse()
+    }
+}
+
+impl fmt::Display for Stat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Attack => "attack",
+            Self::Defense => "defense",
+            Self::Speed => "speed",
+            Self::Dodge => "dodge",
+            Self::Accuracy => "accuracy",
+        })
+    }
+}
+
+/// Lasting condition of a creature. A creature can only suffer from one at a time.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(try_from = "String")]
+pub enum Status {
+    /// Lose 1/16 of max HP each turn
+    Burn,
+    /// Cannot act
+    Freeze,
+    /// Lose 1/8 of max HP each turn
+    Poison,
+}
+
+impl FromStr for Status {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, String> {
+        Ok(match value.to_ascii_lowercase().as_str() {
+            "burn" => Self::Burn,
+            "freeze" => Self::Freeze,
+            "poison" => Self::Poison,
+            _ => return Err(format!("unknown status {value}")),
+        })
+    }
+}
+
+impl TryFrom<String> for Status {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, String> {
+        value.parse()
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Burn => "burnt",
+            Self::Freeze => "frozen",
+            Self::Poison => "poisoned",
+        })
     }
 }
 
 /// In-fight effect altering status (frozen, burnt..) or stats
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "kind", deny_unknown_fields)]
 pub enum Effect {
-    // StatusChange(Status),
-    // StatsChange(&str, f32, usize),
-    // ...
+    /// Raise or lower a stat by some stages, for some turns.
+    #[serde(rename = "stat")]
+    StatsChange { stat: Stat, stages: i8, turns: u8 },
+    /// Inflict a status for some turns, `chance` being a %.
+    #[serde(rename = "status")]
+    StatusChange {
+        status: Status,
+        turns: u8,
+        #[serde(default = "always")]
+        chance: u8,
+    },
+    /// Remove lowered stats and statuses.
+    #[serde(rename = "cleanse")]
+    Cleanse,
+    /// Restore some HP, `percent` being a % of the max HP.
+    #[serde(rename = "heal")]
+    Heal { percent: u8 },
+}
+
+fn always() -> u8 {
+    100
 }
 
+/// Base chance of a physical attack to be a critical hit, in %.
+const PHYSICAL_CRIT_CHANCE: u32 = 15;
+/// Damage gained by weight based attacks each time the attacker doubles the mass of the defender.
+const WEIGHT_BONUS_PER_DOUBLING: f32 = 0.25;
+/// Bounds of the damage multiplier of weight based attacks.
+const MIN_WEIGHT_MULTIPLIER: f32 = 0.5;
+const MAX_WEIGHT_MULTIPLIER: f32 = 2.;
+
 /// Attacks derived from physical `Attribute`s
 #[derive(Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
 pub(crate) struct PhysicalAttack {
+    pub id: AttackId,
     pub name: String,
     /// Required attributes to be able to use it.
     pub source: Vec<Attribute>,
@@ -60,82 +189,70 @@ pub(crate) struct PhysicalAttack {
     pub weak_against: Option<Vec<Attribute>>,
     // no dmg
     pub useless_against: Option<Vec<Attribute>>,
+    #[serde(rename = "target")]
     pub target_type: Target,
     pub damage: Option<u8>,
     pub effects: Option<Vec<Effect>>,
-}
-
-impl PhysicalAttack {
-    // todo: load for real
-    pub fn from_value(value: &serde_json::Value) -> Self {
-        Self {
-            name: value
-                .get("name")
-                .expect("should have a name")
-                .as_str()
-                .unwrap()
-                .to_string(),
-            source: value
-                .get("source")
-                .expect("should have source")
-                .as_array()
-                .unwrap()
-                .iter()
-                .map(|attr| Attribute::from_value(attr))
-                .collect::<Vec<Attribute>>(),
-            strong_against: None,
-            weak_against: None,
-            useless_against: None,
-            target_type: value.get("target").unwrap().as_str().unwrap().into(),
-            damage: if let Some(v) = value.get("damage") {
-                Some(v.as_u64().unwrap() as u8)
-            } else {
-                None
-            },
-            effects: None,
-        }
-    }
+    /// Heavier attackers hit harder with it, lighter ones softer.
+    #[serde(default)]
+    pub weight_based: bool,
 }
 
 /// Each creature gets a magical attack based on their element.
-#[derive(Clone)]
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
 pub(crate) struct MagicalAttack {
+    pub id: AttackId,
     pub name: String,
     pub element: Element,
     pub damage: Option<u8>,
-}
-
-impl MagicalAttack {
-    pub fn from_value(value: &serde_json::Value) -> Self {
-        MagicalAttack {
-            name: value
-                .get("name")
-                .expect("should have a name")
-                .as_str()
-                .unwrap()
-                .to_string(),
-            element: value["element"]
-                .as_str()
-                .expect("creature element should be a string")
-                .into(),
-            damage: if let Some(v) = value.get("damage") {
-                Some(v.as_u64().unwrap() as u8)
-            } else {
-                None
-            },
-        }
-    }
+    pub effects: Option<Vec<Effect>>,
+    /// Attributes of which the species needs one, e.g. fire needs a tail, wings or scales.
+    #[serde(default)]
+    pub unlocked_by: Vec<Attribute>,
 }
 
 /// Common trait for physical and magical attacks
 pub trait Attack {
+    fn id(&self) -> AttackId;
     fn name(&self) -> String;
     fn attributes(&self) -> Vec<Attribute>;
     fn element(&self) -> Option<Element>;
     fn damage(&self) -> Option<u8>;
+    fn effects(&self) -> Vec<Effect>;
+    fn target_type(&self) -> Target;
+    /// Chance to land a critical hit, in %.
+    fn crit_chance(&self) -> u32 {
+        0
+    }
+    /// Damage multiplier against a creature having the given attributes.
+    fn attributes_multiplier(&self, _defender: &[Attribute]) -> f32 {
+        1.
+    }
+    /// Damage multiplier from the masses of the attacker and the defender, in kg.
+    fn weight_multiplier(&self, _attacker: f32, _defender: f32) -> f32 {
+        1.
+    }
+    /// Attributes of which a species needs one to learn it, none if empty.
+    fn unlocked_by(&self) -> Vec<Attribute> {
+        vec![]
+    }
+    /// Whether a species having these attributes can learn it: it needs all the attributes
+    /// the attack comes from, and one of the ones unlocking it.
+    fn is_learnable_with(&self, attributes: &[Attribute]) -> bool {
+        let unlocked_by = self.unlocked_by();
+        self.attributes()
+            .iter()
+            .all(|attr| attributes.contains(attr))
+            && (unlocked_by.is_empty() || unlocked_by.iter().any(|attr| attributes.contains(attr)))
+    }
 }
 
 impl Attack for PhysicalAttack {
+    fn id(&self) -> AttackId {
+        self.id.clone()
+    }
+
     fn name(&self) -> String {
         self.name.clone()
     }
@@ -151,9 +268,57 @@ impl Attack for PhysicalAttack {
     fn damage(&self) -> Option<u8> {
         self.damage
     }
+
+    fn effects(&self) -> Vec<Effect> {
+        self.effects.clone().unwrap_or_default()
+    }
+
+    fn target_type(&self) -> Target {
+        self.target_type
+    }
+
+    fn crit_chance(&self) -> u32 {
+        PHYSICAL_CRIT_CHANCE
+    }
+
+    /// Useless if the defender has any of the `useless_against` attributes, otherwise x2 if
+    /// it has any of the `strong_against` ones, and x0.5 if it has any of the `weak_against` ones.
+    fn attributes_multiplier(&self, defender: &[Attribute]) -> f32 {
+        let matches = |attrs: &Option<Vec<Attribute>>| {
+            attrs
+                .as_ref()
+                .is_some_and(|attrs| attrs.iter().any(|attr| defender.contains(attr)))
+        };
+        if matches(&self.useless_against) {
+            return 0.;
+        }
+        let mut multiplier = 1.;
+        if matches(&self.strong_against) {
+            multiplier *= 2.;
+        }
+        if matches(&self.weak_against) {
+            multiplier *= 0.5;
+        }
+        multiplier
+    }
+
+    /// Weight based attacks get 25% stronger each time the attacker doubles the mass of
+    /// the defender, and weaker the other way around, from x0.5 to x2.
+    fn weight_multiplier(&self, attacker: f32, defender: f32) -> f32 {
+        if !self.weight_based {
+            return 1.;
+        }
+        let doublings = (attacker / defender).log2();
+        (1. + doublings * WEIGHT_BONUS_PER_DOUBLING)
+            .clamp(MIN_WEIGHT_MULTIPLIER, MAX_WEIGHT_MULTIPLIER)
+    }
 }
 
 impl Attack for MagicalAttack {
+    fn id(&self) -> AttackId {
+        self.id.clone()
+    }
+
     fn name(&self) -> String {
         self.name.clone()
     }
@@ -169,6 +334,19 @@ impl Attack for MagicalAttack {
     fn damage(&self) -> Option<u8> {
         self.damage
     }
+
+    fn effects(&self) -> Vec<Effect> {
+        self.effects.clone().unwrap_or_default()
+    }
+
+    /// Magical attacks always aim at one enemy.
+    fn target_type(&self) -> Target {
+        Target::Enemy
+    }
+
+    fn unlocked_by(&self) -> Vec<Attribute> {
+        self.unlocked_by.clone()
+    }
 }
 
 /// Wrapper for storing all attacks
@@ -181,30 +359,25 @@ impl Attacks {
         let attacks = self
             .0
             .iter()
-            .filter(|atk| {
-                atk.element().is_none()
-                    && atk
-                        .attributes()
-                        .iter()
-                        .all(|attr| species.attributes.contains(attr))
-            })
-            .map(|arc| Arc::clone(arc))
+            .filter(|atk| atk.element().is_none() && atk.is_learnable_with(&species.attributes))
+            .map(Arc::clone)
             .collect();
         Attacks(attacks)
     }
 
-    pub fn filter_by_elem(&self, element: Element) -> Self {
+    /// Attacks of the element that the attributes of the species unlock.
+    pub fn filter_by_elem(&self, element: Element, species: &Species) -> Self {
         let attacks = self
             .0
             .iter()
             .filter(|atk| {
                 if let Some(elt) = atk.element() {
-                    elt == element
+                    elt == element && atk.is_learnable_with(&species.attributes)
                 } else {
                     false
                 }
             })
-            .map(|arc| Arc::clone(arc))
+            .map(Arc::clone)
             .collect();
         Attacks(attacks)
     }
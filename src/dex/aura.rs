@@ -0,0 +1,54 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+/// Personality of a creature, or of the player. Taming a creature depends on its aura,
+/// and gets easier when the player shares it.
+#[derive(Copy, Clone, Serialize, Deserialize, Default, PartialEq, Eq, Hash, Debug)]
+#[serde(try_from = "String")]
+pub enum Aura {
+    Aggressive,
+    #[default]
+    Gentle,
+    Curious,
+    Wise,
+    Playful,
+    Reckless,
+}
+
+impl FromStr for Aura {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, String> {
+        Ok(match value.to_ascii_lowercase().as_str() {
+            "aggressive" => Aura::Aggressive,
+            "gentle" => Aura::Gentle,
+            "curious" => Aura::Curious,
+            "wise" => Aura::Wise,
+            "playful" => Aura::Playful,
+            "reckless" => Aura::Reckless,
+            _ => return Err(format!("unknown aura {value}")),
+        })
+    }
+}
+
+impl TryFrom<String> for Aura {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, String> {
+        value.parse()
+    }
+}
+
+impl fmt::Display for Aura {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Aggressive => "Aggressive",
+            Self::Gentle => "Gentle",
+            Self::Curious => "Curious",
+            Self::Wise => "Wise",
+            Self::Playful => "Playful",
+            Self::Reckless => "Reckless",
+        })
+    }
+}
@@ -0,0 +1,362 @@
+use std::{collections::HashMap, fmt};
+
+use bevy::{asset::Asset, reflect::TypePath};
+use serde::{Deserialize, Deserializer, de::DeserializeOwned};
+use serde_json::Value;
+
+use super::{
+    Aura, Effect, Element, Evolution, Stats,
+    attacks::{MagicalAttack, PhysicalAttack},
+    ids::{CreatureId, SpeciesId},
+    species::{Attribute, Learnable},
+};
+
+// Schemas of the JSON files of `assets/creatures/`. Serde checks the types and the names
+// of the attributes, elements, etc., what it cannot check is done by the `check` functions.
+
+/// Something wrong in the data files, and where it is.
+#[derive(Debug, Clone)]
+pub struct DexError {
+    /// JSON path, such as `gen1.creatures.json: species[0].individuals[2].element`.
+    pub path: String,
+    pub message: String,
+}
+
+impl DexError {
+    pub(crate) fn new(path: &str, message: impl ToString) -> Self {
+        DexError {
+            path: path.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    /// Error found by serde, its path being relative to `prefix`.
+    fn from_serde(prefix: &str, error: serde_path_to_error::Error<serde_json::Error>) -> Self {
+        let path = join(prefix, &error.path().to_string());
+        DexError::new(&path, error.into_inner())
+    }
+}
+
+/// Append `path` to `prefix`, `file.json:` being the root of a file.
+fn join(prefix: &str, path: &str) -> String {
+    if path.is_empty() || path == "." {
+        prefix.to_string()
+    } else if path.starts_with('[') {
+        format!("{prefix}{path}")
+    } else if prefix.ends_with(':') {
+        format!("{prefix} {path}")
+    } else {
+        format!("{prefix}.{path}")
+    }
+}
+
+impl fmt::Display for DexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Read a whole file, `None` if it does not fit the schema at all.
+pub fn parse_file<T: DeserializeOwned>(
+    file: &str,
+    content: &str,
+    errors: &mut Vec<DexError>,
+) -> Option<T> {
+    let deserializer = &mut serde_json::Deserializer::from_str(content);
+    serde_path_to_error::deserialize(deserializer)
+        .map_err(|error| errors.push(DexError::from_serde(&format!("{file}:"), error)))
+        .ok()
+}
+
+/// A list whose entries are read one by one, so that a faulty entry does not hide
+/// the errors of the next ones.
+pub struct Entries<T>(Vec<Result<T, DexError>>);
+
+impl<'de, T: DeserializeOwned> Deserialize<'de> for Entries<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let values = Vec::<Value>::deserialize(deserializer)?;
+        Ok(Entries(
+            values
+                .into_iter()
+                .enumerate()
+                .map(|(idx, value)| {
+                    serde_path_to_error::deserialize(value)
+                        .map_err(|error| DexError::from_serde(&format!("[{idx}]"), error))
+                })
+                .collect(),
+        ))
+    }
+}
+
+impl<T> Default for Entries<T> {
+    fn default() -> Self {
+        Entries(vec![])
+    }
+}
+
+impl<T> Entries<T> {
+    /// Entries read successfully, with their path. The errors of the others go to `errors`.
+    pub fn collect(&self, prefix: &str, errors: &mut Vec<DexError>) -> Vec<(String, &T)> {
+        self.0
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, entry)| match entry {
+                Ok(entry) => Some((format!("{prefix}[{idx}]"), entry)),
+                Err(error) => {
+                    errors.push(DexError::new(&join(prefix, &error.path), &error.message));
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+/// Species and their individuals, such as `gen1.creatures.json`.
+#[derive(Asset, TypePath, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreaturesFile {
+    /// Name of the file, for the errors.
+    #[serde(skip)]
+    pub file: String,
+    pub species: Entries<SpeciesData>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SpeciesData {
+    pub id: SpeciesId,
+    pub name: String,
+    pub mass_kg: f32,
+    pub height_m: f32,
+    pub attributes: Vec<Attribute>,
+    pub stats: Stats,
+    pub individuals: Entries<CreatureData>,
+    #[serde(default)]
+    pub learnset: Option<Vec<Learnable>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreatureData {
+    pub id: CreatureId,
+    pub name: String,
+    pub element: Element,
+    pub aura: Aura,
+    #[serde(default)]
+    pub evolutions: Vec<Evolution>,
+}
+
+/// Physical and magical attacks, such as `base.attacks.json`.
+#[derive(Asset, TypePath, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AttacksFile {
+    /// Name of the file, for the errors.
+    #[serde(skip)]
+    pub file: String,
+    pub physical_attacks: Entries<PhysicalAttack>,
+    pub magical_attacks: Entries<MagicalAttack>,
+}
+
+/// Extra species and attacks, such as `packs/example.pack.json`.
+#[derive(Asset, TypePath, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PackFile {
+    /// Name of the file, for the errors.
+    #[serde(skip)]
+    pub file: String,
+    /// Why the file could not be read, if so.
+    #[serde(skip)]
+    pub errors: Vec<DexError>,
+    pub name: String,
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub species: Entries<SpeciesData>,
+    #[serde(default)]
+    pub physical_attacks: Entries<PhysicalAttack>,
+    #[serde(default)]
+    pub magical_attacks: Entries<MagicalAttack>,
+}
+
+/// Packs are enabled unless told otherwise.
+fn enabled() -> bool {
+    true
+}
+
+/// Strengths and weaknesses of the elements, such as `base.elements.json`.
+#[derive(Asset, TypePath, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ElementsFile {
+    /// Name of the file, for the errors.
+    #[serde(skip)]
+    pub file: String,
+    pub effectiveness: HashMap<Element, HashMap<Element, f32>>,
+}
+
+impl SpeciesData {
+    pub fn check(&self, path: &str, errors: &mut Vec<DexError>) {
+        if self.mass_kg <= 0. {
+            errors.push(DexError::new(
+                &format!("{path}.mass_kg"),
+                "should be positive",
+            ));
+        }
+        if self.height_m <= 0. {
+            errors.push(DexError::new(
+                &format!("{path}.height_m"),
+                "should be positive",
+            ));
+        }
+        check_stats(&format!("{path}.stats"), &self.stats, errors);
+    }
+}
+
+fn check_stats(path: &str, stats: &Stats, errors: &mut Vec<DexError>) {
+    if stats.hp == 0 {
+        errors.push(DexError::new(&format!("{path}.hp"), "should be positive"));
+    }
+    for (name, percent) in [("dodge", stats.dodge), ("accuracy", stats.accuracy)] {
+        if percent > 100 {
+            errors.push(DexError::new(
+                &format!("{path}.{name}"),
+                format!("{percent} is not a percentage"),
+            ));
+        }
+    }
+}
+
+/// Check the damage and effects of an attack.
+pub fn check_attack(
+    path: &str,
+    damage: Option<u8>,
+    effects: &[Effect],
+    errors: &mut Vec<DexError>,
+) {
+    if damage == Some(0) {
+        errors.push(DexError::new(
+            &format!("{path}.damage"),
+            "should be positive, or left out",
+        ));
+    }
+    check_effects(&format!("{path}.effects"), effects, errors);
+}
+
+pub fn check_effects(path: &str, effects: &[Effect], errors: &mut Vec<DexError>) {
+    for (idx, effect) in effects.iter().enumerate() {
+        let path = format!("{path}[{idx}]");
+        let turns = match *effect {
+            Effect::StatsChange { stages, turns, .. } => {
+                if stages == 0 {
+                    errors.push(DexError::new(&format!("{path}.stages"), "should not be 0"));
+                }
+                turns
+            }
+            Effect::StatusChange { turns, chance, .. } => {
+                if chance == 0 || chance > 100 {
+                    errors.push(DexError::new(
+                        &format!("{path}.chance"),
+                        format!("{chance} should be between 1 and 100"),
+                    ));
+                }
+                turns
+            }
+            Effect::Cleanse => continue,
+            Effect::Heal { percent } => {
+                if percent == 0 || percent > 100 {
+                    errors.push(DexError::new(
+                        &format!("{path}.percent"),
+                        format!("{percent} should be between 1 and 100"),
+                    ));
+                }
+                continue;
+            }
+        };
+        if turns == 0 {
+            errors.push(DexError::new(
+                &format!("{path}.turns"),
+                "should be positive",
+            ));
+        }
+    }
+}
+
+impl ElementsFile {
+    pub fn check(&self, errors: &mut Vec<DexError>) {
+        for (attacker, defenders) in self.effectiveness.iter() {
+            for (defender, multiplier) in defenders {
+                if *multiplier < 0. {
+                    errors.push(DexError::new(
+                        &format!("{}: effectiveness.{attacker}.{defender}", self.file),
+                        "should not be negative",
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// Report every value used by more than one entry, such as ids or names.
+/// `entries` are the paths of the values, and the values.
+pub fn check_unique<'a>(
+    what: &str,
+    entries: impl IntoIterator<Item = (&'a str, &'a str)>,
+    errors: &mut Vec<DexError>,
+) {
+    let mut seen: HashMap<&str, &str> = HashMap::new();
+    for (path, value) in entries {
+        if let Some(first) = seen.get(value) {
+            errors.push(DexError::new(
+                path,
+                format!("{what} {value} is already used by {first}"),
+            ));
+        } else {
+            seen.insert(value, path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_faulty_entry_is_reported_with_its_path() {
+        let content = r#"{
+            "name": "Broken pack",
+            "species": [
+                { "id": "a", "name": "A", "mass_kg": "heavy", "height_m": 1.0,
+                  "attributes": [], "stats": { "hp": 1, "attack": 1, "defense": 1, "speed": 1 },
+                  "individuals": [] },
+                { "id": "b", "name": "B", "mass_kg": 1.0, "height_m": 1.0,
+                  "attributes": [], "stats": { "hp": 1, "attack": 1, "defense": 1, "speed": 1 },
+                  "individuals": [] },
+                { "id": "c", "name": "C", "mass_kg": 1.0, "height_m": 1.0,
+                  "attributes": ["Wings"], "stats": { "hp": 1, "attack": 1, "defense": 1 },
+                  "individuals": [] }
+            ],
+            "physical_attacks": [
+                { "id": "poke", "name": "Poke", "source": ["Claws"], "target": "Nobody" }
+            ]
+        }"#;
+        let mut errors = Vec::new();
+        let pack: PackFile = parse_file("broken.pack.json", content, &mut errors).unwrap();
+        assert!(errors.is_empty(), "{errors:?}");
+        let species = pack
+            .species
+            .collect("broken.pack.json: species", &mut errors);
+        let attacks = pack
+            .physical_attacks
+            .collect("broken.pack.json: physical_attacks", &mut errors);
+        assert_eq!(species.len(), 1);
+        assert!(attacks.is_empty());
+        let paths: Vec<&str> = errors.iter().map(|error| error.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "broken.pack.json: species[0].mass_kg",
+                "broken.pack.json: species[2].stats",
+                "broken.pack.json: physical_attacks[0].target",
+            ]
+        );
+    }
+}
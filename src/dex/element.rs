@@ -1,10 +1,11 @@
-use std::fmt;
+use std::{collections::HashMap, fmt, str::FromStr};
 
 use serde::{Deserialize, Serialize};
 
 /// There are 4 elements in the game. They have circular stregths/weaknesses, and slithgly
 /// alter the stats of a creature.
-#[derive(Copy, Clone, Serialize, Deserialize, Default, PartialEq, Debug)]
+#[derive(Copy, Clone, Serialize, Deserialize, Default, PartialEq, Eq, Hash, Debug)]
+#[serde(try_from = "String")]
 pub enum Element {
     #[default]
     Fire,
@@ -13,15 +14,32 @@ pub enum Element {
     Water,
 }
 
-impl From<&str> for Element {
-    fn from(value: &str) -> Self {
-        match value.to_ascii_lowercase().as_str() {
+impl Element {
+    /// Damage multiplier of an attack of this element against a creature of the `defender` element.
+    pub fn multiplier_against(&self, defender: Element, chart: &ElementChart) -> f32 {
+        chart.0.get(&(*self, defender)).copied().unwrap_or(1.)
+    }
+}
+
+impl FromStr for Element {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, String> {
+        Ok(match value.to_ascii_lowercase().as_str() {
             "fire" => Element::Fire,
             "air" => Element::Air,
             "earth" => Element::Earth,
             "water" => Element::Water,
-            x => panic!("Unknown element type {x}"),
-        }
+            _ => return Err(format!("unknown element {value}")),
+        })
+    }
+}
+
+impl TryFrom<String> for Element {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, String> {
+        value.parse()
     }
 }
 
@@ -35,3 +53,57 @@ impl fmt::Display for Element {
         })
     }
 }
+
+/// Strengths and weaknesses between elements, as (attacker, defender) -> multiplier.
+/// Missing pairs are neutral.
+#[derive(Clone, Default)]
+pub struct ElementChart(HashMap<(Element, Element), f32>);
+
+impl ElementChart {
+    /// Build the chart from the multipliers of each attacker against each defender.
+    pub fn new(effectiveness: HashMap<Element, HashMap<Element, f32>>) -> Self {
+        let chart = effectiveness
+            .into_iter()
+            .flat_map(|(attacker, defenders)| {
+                defenders
+                    .into_iter()
+                    .map(move |(defender, multiplier)| ((attacker, defender), multiplier))
+            })
+            .collect();
+        ElementChart(chart)
+    }
+}
+
+/// How well an attack fares against its target, mostly for the UI.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Effectiveness {
+    Useless,
+    NotVeryEffective,
+    Normal,
+    SuperEffective,
+}
+
+impl From<f32> for Effectiveness {
+    fn from(multiplier: f32) -> Self {
+        if multiplier <= 0. {
+            Self::Useless
+        } else if multiplier < 1. {
+            Self::NotVeryEffective
+        } else if multiplier > 1. {
+            Self::SuperEffective
+        } else {
+            Self::Normal
+        }
+    }
+}
+
+impl fmt::Display for Effectiveness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Useless => "no effect",
+            Self::NotVeryEffective => "not very effective",
+            Self::Normal => "effective",
+            Self::SuperEffective => "super effective",
+        })
+    }
+}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Aura, CreatureId};
+
+/// A creature turning into another one, of its species or not, once its triggers are met.
+/// Every trigger given must be met, and at least one must be given.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Evolution {
+    pub into: CreatureId,
+    /// Level to reach.
+    #[serde(default)]
+    pub level: Option<u8>,
+    /// Item to use on the creature, out of fights.
+    #[serde(default)]
+    pub item: Option<String>,
+    /// Aura the player must have.
+    #[serde(default)]
+    pub aura: Option<Aura>,
+}
+
+impl Evolution {
+    /// Whether a creature at `level`, whose player has `aura`, evolves when `item` is used on it,
+    /// or after gaining XP when there is no item.
+    pub fn is_triggered(&self, level: u8, aura: Aura, item: Option<&str>) -> bool {
+        self.level.is_none_or(|needed| level >= needed)
+            && self.aura.is_none_or(|needed| aura == needed)
+            && self.item.as_deref() == item
+    }
+
+    pub fn has_trigger(&self) -> bool {
+        self.level.is_some() || self.item.is_some() || self.aura.is_some()
+    }
+}
@@ -0,0 +1,462 @@
+use std::{fmt, marker::PhantomData};
+
+use bevy::{
+    asset::{
+        Asset, AssetEvent, AssetLoader, AssetServer, Assets, Handle, LoadContext, LoadState,
+        LoadedFolder, io::Reader,
+    },
+    ecs::{
+        event::EventReader,
+        resource::Resource,
+        system::{Commands, Local, Res, SystemParam},
+    },
+    state::state::State,
+};
+use serde::de::DeserializeOwned;
+
+use crate::{
+    AppState,
+    dex::{
+        Creature, CreatureId, Dex, DexError, PackState,
+        data::{AttacksFile, CreaturesFile, ElementsFile, Entries, PackFile, parse_file},
+    },
+    evolution::PendingEvolutions,
+    fight::{Battle, Trainer},
+    team::{Team, TeamMember},
+};
+
+/// Where the data files live, in `assets/`.
+const CREATURES_PATH: &str = "creatures/gen1.creatures.json";
+const ATTACKS_PATH: &str = "creatures/base.attacks.json";
+const ELEMENTS_PATH: &str = "creatures/base.elements.json";
+/// Every `*.pack.json` in there is a pack.
+const PACKS_PATH: &str = "creatures/packs";
+
+/// A data file read by a `DexFileLoader`, which remembers its name for the errors.
+/// Each kind of file has its own extension, so that the asset server knows its loader.
+pub trait DexFile: Asset + DeserializeOwned {
+    const EXTENSIONS: &'static [&'static str];
+
+    fn set_file(&mut self, file: String);
+
+    /// What stands for a file which could not be read, `None` to fail its loading.
+    fn from_errors(_file: String, _errors: Vec<DexError>) -> Option<Self> {
+        None
+    }
+}
+
+impl DexFile for CreaturesFile {
+    const EXTENSIONS: &'static [&'static str] = &["creatures.json"];
+
+    fn set_file(&mut self, file: String) {
+        self.file = file;
+    }
+}
+
+impl DexFile for AttacksFile {
+    const EXTENSIONS: &'static [&'static str] = &["attacks.json"];
+
+    fn set_file(&mut self, file: String) {
+        self.file = file;
+    }
+}
+
+impl DexFile for ElementsFile {
+    const EXTENSIONS: &'static [&'static str] = &["elements.json"];
+
+    fn set_file(&mut self, file: String) {
+        self.file = file;
+    }
+}
+
+impl DexFile for PackFile {
+    const EXTENSIONS: &'static [&'static str] = &["pack.json"];
+
+    fn set_file(&mut self, file: String) {
+        self.file = file;
+    }
+
+    /// A broken pack would fail the whole folder, it is left out of the dex instead.
+    fn from_errors(file: String, errors: Vec<DexError>) -> Option<Self> {
+        Some(PackFile {
+            name: file.clone(),
+            file,
+            enabled: true,
+            errors,
+            species: Entries::default(),
+            physical_attacks: Entries::default(),
+            magical_attacks: Entries::default(),
+        })
+    }
+}
+
+/// Read a JSON data file into its schema. Each kind of file has its own extension, such as
+/// `.creatures.json`, so that the asset server picks the right loader, and a whole folder
+/// of packs can be loaded.
+pub struct DexFileLoader<T>(PhantomData<T>);
+
+impl<T> Default for DexFileLoader<T> {
+    fn default() -> Self {
+        DexFileLoader(PhantomData)
+    }
+}
+
+/// Why a data file could not be loaded.
+#[derive(Debug)]
+pub enum DexFileError {
+    Io(std::io::Error),
+    Data(Vec<DexError>),
+}
+
+impl fmt::Display for DexFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DexFileError::Io(error) => write!(f, "{error}"),
+            DexFileError::Data(errors) => {
+                let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+                write!(f, "{}", errors.join("\n"))
+            }
+        }
+    }
+}
+
+impl std::error::Error for DexFileError {}
+
+impl From<std::io::Error> for DexFileError {
+    fn from(error: std::io::Error) -> Self {
+        DexFileError::Io(error)
+    }
+}
+
+impl<T: DexFile> AssetLoader for DexFileLoader<T> {
+    type Asset = T;
+    type Settings = ();
+    type Error = DexFileError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<T, DexFileError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let file = load_context
+            .path()
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let content = String::from_utf8_lossy(&bytes);
+        let mut errors = Vec::new();
+        match parse_file::<T>(&file, &content, &mut errors) {
+            Some(mut asset) => {
+                asset.set_file(file);
+                Ok(asset)
+            }
+            None => T::from_errors(file, errors.clone()).ok_or(DexFileError::Data(errors)),
+        }
+    }
+
+    fn extensions(&self) -> &[&str] {
+        T::EXTENSIONS
+    }
+}
+
+/// Handles of the data files the `Dex` is built from.
+#[derive(Resource)]
+pub struct DexFiles {
+    creatures: Handle<CreaturesFile>,
+    attacks: Handle<AttacksFile>,
+    elements: Handle<ElementsFile>,
+    /// Fails to load on platforms which cannot list a folder, such as the web.
+    packs: Handle<LoadedFolder>,
+}
+
+/// Start loading the data files, the `Dex` is built once they are all there.
+pub fn load_dex_files(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(DexFiles {
+        creatures: asset_server.load(CREATURES_PATH),
+        attacks: asset_server.load(ATTACKS_PATH),
+        elements: asset_server.load(ELEMENTS_PATH),
+        packs: asset_server.load_folder(PACKS_PATH),
+    });
+}
+
+/// Whether the file is now ready to be read, for the first time or again.
+fn changed<A: Asset>(event: &AssetEvent<A>) -> bool {
+    matches!(
+        event,
+        AssetEvent::LoadedWithDependencies { .. } | AssetEvent::Modified { .. }
+    )
+}
+
+/// The packs of the folder, `None` while some are loading.
+fn read_packs<'a>(
+    files: &DexFiles,
+    folders: &Assets<LoadedFolder>,
+    packs: &'a Assets<PackFile>,
+    asset_server: &AssetServer,
+) -> Option<Vec<&'a PackFile>> {
+    match asset_server.get_load_state(&files.packs) {
+        Some(LoadState::Loaded) => {}
+        // no folder, no packs
+        Some(LoadState::Failed(_)) => return Some(vec![]),
+        _ => return None,
+    }
+    let mut pack_files = folders
+        .get(&files.packs)?
+        .handles
+        .iter()
+        .filter_map(|handle| handle.clone().try_typed::<PackFile>().ok())
+        .map(|handle| packs.get(&handle))
+        .collect::<Option<Vec<_>>>()?;
+    pack_files.sort_by(|a, b| a.file.cmp(&b.file));
+    Some(pack_files)
+}
+
+/// Creatures and attacks the game currently refers to, which a new `Dex` must still know.
+#[derive(SystemParam)]
+pub struct IdsInUse<'w> {
+    state: Option<Res<'w, State<AppState>>>,
+    team: Option<Res<'w, Team>>,
+    battle: Option<Res<'w, Battle>>,
+    trainer: Option<Res<'w, Trainer>>,
+    pending_evolutions: Option<Res<'w, PendingEvolutions>>,
+    wild_creature: Option<Res<'w, Creature>>,
+}
+
+impl IdsInUse<'_> {
+    /// Fights hold on to their creatures and attacks, the `Dex` waits for them to end.
+    fn in_fight(&self) -> bool {
+        self.state
+            .as_ref()
+            .is_some_and(|state| *state.get() == AppState::InFight)
+    }
+
+    /// Creatures and attacks in use that `dex` does not know, such as `attack kick`.
+    fn missing(&self, dex: &Dex) -> Vec<String> {
+        let team = self.team.iter().flat_map(|team| team.0.iter());
+        let battle = self
+            .battle
+            .iter()
+            .flat_map(|battle| battle.combatants.iter().map(|combatant| &combatant.member));
+        let trainer = self.trainer.iter().flat_map(|trainer| trainer.team.iter());
+        let evolutions = self
+            .pending_evolutions
+            .iter()
+            .flat_map(|pending| pending.0.iter())
+            .flat_map(|evolution| [&evolution.from, &evolution.into]);
+        let wild = self.wild_creature.iter().map(|creature| &creature.id);
+        missing_ids(
+            team.chain(battle).chain(trainer),
+            evolutions.chain(wild),
+            dex,
+        )
+    }
+}
+
+/// Creatures and attacks of `members`, and `creatures`, that `dex` does not know.
+fn missing_ids<'a>(
+    members: impl Iterator<Item = &'a TeamMember>,
+    creatures: impl Iterator<Item = &'a CreatureId>,
+    dex: &Dex,
+) -> Vec<String> {
+    let mut missing = Vec::new();
+    for member in members {
+        if dex.get_creature(&member.creature_id).is_none() {
+            missing.push(format!("creature {}", member.creature_id));
+        }
+        for id in member.moves.iter() {
+            if dex.get_attack(id).is_none() {
+                missing.push(format!("attack {id}"));
+            }
+        }
+    }
+    for id in creatures {
+        if dex.get_creature(id).is_none() {
+            missing.push(format!("creature {id}"));
+        }
+    }
+    missing.sort();
+    missing.dedup();
+    missing
+}
+
+/// Build the `Dex` once the data files are loaded, and again whenever one of them changes.
+///
+/// The game cannot run without its data, so the errors of the first build are fatal.
+/// Later on, a faulty edit is reported and the previous `Dex` is kept, and so it is when
+/// creatures or attacks in use would go missing. Changes made during a fight wait for its end.
+#[allow(clippy::too_many_arguments)]
+pub fn build_dex(
+    mut commands: Commands,
+    mut creatures_events: EventReader<AssetEvent<CreaturesFile>>,
+    mut attacks_events: EventReader<AssetEvent<AttacksFile>>,
+    mut elements_events: EventReader<AssetEvent<ElementsFile>>,
+    mut packs_events: EventReader<AssetEvent<PackFile>>,
+    mut folders_events: EventReader<AssetEvent<LoadedFolder>>,
+    mut pending: Local<bool>,
+    files: Res<DexFiles>,
+    creatures: Res<Assets<CreaturesFile>>,
+    attacks: Res<Assets<AttacksFile>>,
+    elements: Res<Assets<ElementsFile>>,
+    packs: Res<Assets<PackFile>>,
+    folders: Res<Assets<LoadedFolder>>,
+    asset_server: Res<AssetServer>,
+    dex: Option<Res<Dex>>,
+    in_use: IdsInUse,
+) {
+    // every reader is drained, the files may change again while others are still loading
+    *pending |= creatures_events
+        .read()
+        .filter(|event| changed(event))
+        .count()
+        + attacks_events.read().filter(|event| changed(event)).count()
+        + elements_events
+            .read()
+            .filter(|event| changed(event))
+            .count()
+        + packs_events.read().filter(|event| changed(event)).count()
+        + folders_events.read().filter(|event| changed(event)).count()
+        > 0;
+    if dex.is_none() {
+        let states = [
+            asset_server.get_load_state(&files.creatures),
+            asset_server.get_load_state(&files.attacks),
+            asset_server.get_load_state(&files.elements),
+        ];
+        for state in states.into_iter().flatten() {
+            if let LoadState::Failed(error) = state {
+                panic!("{error}");
+            }
+        }
+    }
+    if !*pending || (dex.is_some() && in_use.in_fight()) {
+        return;
+    }
+    let (Some(creatures), Some(attacks), Some(elements), Some(pack_files)) = (
+        creatures.get(&files.creatures),
+        attacks.get(&files.attacks),
+        elements.get(&files.elements),
+        read_packs(&files, &folders, &packs, &asset_server),
+    ) else {
+        return;
+    };
+    *pending = false;
+    match Dex::new(creatures, attacks, elements, &pack_files, &asset_server) {
+        Ok(new_dex) => {
+            let missing = in_use.missing(&new_dex);
+            if !missing.is_empty() {
+                for id in missing.iter() {
+                    eprintln!("The {id} is in use but missing from the dex data");
+                }
+                eprintln!("Keeping the previous dex");
+                return;
+            }
+            if dex.is_some() {
+                println!("Dex reloaded");
+            }
+            for pack in new_dex.packs.iter() {
+                if let PackState::Rejected(errors) = &pack.state {
+                    for error in errors.iter() {
+                        eprintln!("{error}");
+                    }
+                    eprintln!("Pack {} left out", pack.name);
+                }
+            }
+            commands.insert_resource(new_dex);
+        }
+        Err(errors) => {
+            for error in errors.iter() {
+                eprintln!("{error}");
+            }
+            if dex.is_none() {
+                panic!("{} errors in the dex data", errors.len());
+            }
+            eprintln!(
+                "{} errors in the dex data, keeping the previous one",
+                errors.len()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+impl Dex {
+    /// The dex of the base files, read right away and without packs.
+    pub fn from_base_files() -> Dex {
+        Dex::with_packs(&[]).unwrap_or_else(|errors| panic!("{errors:?}"))
+    }
+
+    /// The dex of the base files and of `packs`, given as their file name and content.
+    pub fn with_packs(packs: &[(&str, &str)]) -> Result<Dex, Vec<DexError>> {
+        use bevy::{
+            MinimalPlugins,
+            app::App,
+            asset::{AssetApp, AssetPlugin},
+        };
+        use bevy_easy_gif::prelude::GifAsset;
+
+        fn parse<T: DexFile>(file: &str, content: &str) -> T {
+            let mut errors = Vec::new();
+            let mut parsed = parse_file(file, content, &mut errors)
+                .or_else(|| T::from_errors(file.to_string(), errors.clone()))
+                .unwrap_or_else(|| panic!("{errors:?}"));
+            parsed.set_file(file.to_string());
+            parsed
+        }
+
+        fn read<T: DexFile>(path: &str) -> T {
+            parse(
+                path,
+                &std::fs::read_to_string(format!("assets/{path}")).unwrap(),
+            )
+        }
+
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<GifAsset>();
+        let creatures: CreaturesFile = read(CREATURES_PATH);
+        let attacks: AttacksFile = read(ATTACKS_PATH);
+        let elements: ElementsFile = read(ELEMENTS_PATH);
+        let pack_files: Vec<PackFile> = packs
+            .iter()
+            .map(|(file, content)| parse(file, content))
+            .collect();
+        let pack_files: Vec<&PackFile> = pack_files.iter().collect();
+        let asset_server = app.world().resource::<AssetServer>();
+        Dex::new(&creatures, &attacks, &elements, &pack_files, asset_server)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::AttackId;
+
+    #[test]
+    fn removed_attacks_in_use_keep_the_previous_dex() {
+        let dex = Dex::from_base_files();
+        let creature = &dex
+            .get_creature(&CreatureId(String::from("flammiaou")))
+            .unwrap()
+            .0;
+        let mut member = TeamMember::new(creature, &dex);
+        assert!(missing_ids([&member].into_iter(), [].into_iter(), &dex).is_empty());
+        member.moves.push(AttackId(String::from("removed")));
+        assert_eq!(
+            missing_ids([&member].into_iter(), [].into_iter(), &dex),
+            ["attack removed"]
+        );
+    }
+
+    #[test]
+    fn removed_creatures_in_use_keep_the_previous_dex() {
+        let dex = Dex::from_base_files();
+        let removed = CreatureId(String::from("removed"));
+        assert_eq!(
+            missing_ids([].into_iter(), [&removed].into_iter(), &dex),
+            ["creature removed"]
+        );
+    }
+}
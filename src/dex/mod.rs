@@ -1,29 +1,47 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use bevy::{
     app::{App, Plugin, PreStartup},
     asset::{AssetServer, Handle},
-    ecs::{
-        resource::Resource,
-        system::{Commands, ResMut},
-    },
+    ecs::resource::Resource,
+    prelude::{AssetApp, IntoScheduleConfigs, Update, resource_exists},
 };
 use bevy_easy_gif::prelude::GifAsset;
-use rand::Rng;
+use rand::{Rng, seq::IndexedRandom};
 
-use crate::{
-    dex::attacks::{MagicalAttack, PhysicalAttack},
-    team::TeamMember,
-};
+use crate::team::{MAX_MOVES, TeamMember};
+
+mod data;
+pub use data::DexError;
+use data::{AttacksFile, CreaturesFile, ElementsFile, PackFile};
+pub(crate) use data::{Entries, check_effects, check_unique, parse_file};
+
+mod loader;
+use loader::{DexFileLoader, DexFiles, build_dex, load_dex_files};
+
+mod pack;
+use pack::{BASE_PACK, Contents};
+pub use pack::{Pack, PackState};
+
+mod ids;
+pub use ids::{AttackId, CreatureId, SpeciesId};
+
+mod aura;
+pub use aura::Aura;
 
 mod element;
+use element::ElementChart;
+pub use element::{Effectiveness, Element};
+
+mod evolution;
+pub use evolution::Evolution;
 
 mod species;
-pub use species::{Creature, Species};
+pub use species::{Creature, Species, Stats, size_dodge};
 
 mod attacks;
-pub use attacks::Attack;
 use attacks::Attacks;
+pub use attacks::{Attack, Effect, Stat, Status, Target};
 
 /// This plugin is responsible for loading all the data relevant to the creatures.
 /// Namely sprites, attacks, elements, species, description...
@@ -32,75 +50,151 @@ pub struct DexPlugin;
 
 impl Plugin for DexPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(PreStartup, init_index);
-        app.init_resource::<Creature>(); // wild encounter // todo to move else where or remove
+        app.init_asset::<CreaturesFile>()
+            .init_asset::<AttacksFile>()
+            .init_asset::<ElementsFile>()
+            .init_asset::<PackFile>()
+            .init_asset_loader::<DexFileLoader<CreaturesFile>>()
+            .init_asset_loader::<DexFileLoader<AttacksFile>>()
+            .init_asset_loader::<DexFileLoader<ElementsFile>>()
+            .init_asset_loader::<DexFileLoader<PackFile>>()
+            .add_systems(PreStartup, load_dex_files)
+            .add_systems(Update, build_dex.run_if(resource_exists::<DexFiles>));
     }
 }
 
-fn init_index(mut commands: Commands, asset_server: ResMut<AssetServer>) {
-    commands.insert_resource(Dex::new(asset_server));
-}
-
 /// Hold all species and creatures in the game
 /// Not a pokedex though, more like an encyclopedia (no discover mechanism)
 #[derive(Resource)]
 pub struct Dex {
     pub species: Vec<Species>,
     pub attacks: Attacks,
+    pub elements: ElementChart,
+    /// Packs found next to the base files, whether they made it or not.
+    pub packs: Vec<Pack>,
+    /// Position of each species in `species`.
+    species_index: HashMap<SpeciesId, usize>,
+    /// Position of each creature: its species in `species`, then among its individuals.
+    creature_index: HashMap<CreatureId, (usize, usize)>,
+    /// Position of each attack in `attacks`.
+    attack_index: HashMap<AttackId, usize>,
 }
 
 impl Dex {
-    pub fn new(asset_server: ResMut<AssetServer>) -> Self {
-        // loading creatures
-        let content = include_str!("../../assets/creatures/gen1.json");
-        let json: serde_json::Value = serde_json::from_str(content).unwrap();
-        let mut species_list: Vec<Species> = Vec::new();
-        for sp in json["species"]
-            .as_array()
-            .expect("species should be an array")
-        {
-            let mut species = Species::from_value(sp);
-            let mut creatures = Vec::new();
-            for cr in sp["individuals"]
-                .as_array()
-                .expect("individuals should be an array")
-            {
-                let creature = Creature::from_value(cr, species_list.len(), &species.stats);
-                let handle: Handle<GifAsset> = asset_server.load(creature.assets_texture_path());
-                creatures.push((creature, handle));
-            }
-            species.individuals = creatures;
-
-            species_list.push(species);
-        }
-        // attacks
-        let content = include_str!("../../assets/creatures/attacks.json");
-        let json: serde_json::Value = serde_json::from_str(content).unwrap();
-        let mut attacks: Attacks = Attacks(vec![]);
-        for pa in json["physical_attacks"]
-            .as_array()
-            .expect("phys atks should be an array")
-        {
-            attacks.0.push(Arc::new(PhysicalAttack::from_value(pa)));
+    /// Gather the loaded data files, and report all their errors if any.
+    /// Packs come on top of the base files: a faulty one is left out, and the others
+    /// still make it.
+    fn new(
+        creatures: &CreaturesFile,
+        attacks_file: &AttacksFile,
+        elements: &ElementsFile,
+        pack_files: &[&PackFile],
+        asset_server: &AssetServer,
+    ) -> Result<Self, Vec<DexError>> {
+        let mut errors = Vec::new();
+        let mut contents = Contents::default();
+        contents.add_species(
+            &creatures.file,
+            &creatures.species,
+            BASE_PACK,
+            asset_server,
+            &mut errors,
+        );
+        contents.add_attacks(
+            &attacks_file.file,
+            &attacks_file.physical_attacks,
+            &attacks_file.magical_attacks,
+            &mut errors,
+        );
+        elements.check(&mut errors);
+        let elements = ElementChart::new(elements.effectiveness.clone());
+        contents.check_unique(&mut errors);
+        contents.check_evolutions(&mut errors);
+        contents.check_learnsets(&mut errors);
+        if !errors.is_empty() {
+            return Err(errors);
         }
-        for ma in json["magical_attacks"]
-            .as_array()
-            .expect("magic atks should be an array")
-        {
-            attacks.0.push(Arc::new(MagicalAttack::from_value(ma)));
+        // packs, in turn, cannot reuse an id or a name of the ones before
+        let mut packs = Vec::new();
+        for pack_file in pack_files {
+            let state = if !pack_file.errors.is_empty() {
+                PackState::Rejected(pack_file.errors.iter().map(ToString::to_string).collect())
+            } else if pack_file.enabled {
+                let mut pack_errors = Vec::new();
+                let mut merged = contents.clone();
+                merged.add_species(
+                    &pack_file.file,
+                    &pack_file.species,
+                    &pack_file.name,
+                    asset_server,
+                    &mut pack_errors,
+                );
+                merged.add_attacks(
+                    &pack_file.file,
+                    &pack_file.physical_attacks,
+                    &pack_file.magical_attacks,
+                    &mut pack_errors,
+                );
+                merged.check_unique(&mut pack_errors);
+                merged.check_evolutions(&mut pack_errors);
+                merged.check_learnsets(&mut pack_errors);
+                if pack_errors.is_empty() {
+                    contents = merged;
+                    PackState::Enabled
+                } else {
+                    PackState::Rejected(pack_errors.iter().map(ToString::to_string).collect())
+                }
+            } else {
+                PackState::Disabled
+            };
+            let pack = Pack {
+                name: pack_file.name.clone(),
+                file: pack_file.file.clone(),
+                state,
+            };
+            packs.push(pack);
         }
-        Dex {
+        let species_list: Vec<Species> = contents.species.into_iter().map(|(_, sp)| sp).collect();
+        let attacks = Attacks(contents.attacks.into_iter().map(|(_, atk)| atk).collect());
+        // lookup maps
+        let species_index = species_list
+            .iter()
+            .enumerate()
+            .map(|(species_pos, species)| (species.id.clone(), species_pos))
+            .collect();
+        let creature_index = species_list
+            .iter()
+            .enumerate()
+            .flat_map(|(species_pos, species)| {
+                species.individuals.iter().enumerate().map(
+                    move |(individual_pos, (creature, _))| {
+                        (creature.id.clone(), (species_pos, individual_pos))
+                    },
+                )
+            })
+            .collect();
+        let attack_index = attacks
+            .0
+            .iter()
+            .enumerate()
+            .map(|(attack_pos, attack)| (attack.id(), attack_pos))
+            .collect();
+        Ok(Dex {
             species: species_list,
             attacks,
-        }
+            elements,
+            packs,
+            species_index,
+            creature_index,
+            attack_index,
+        })
     }
 
     /// return a copy of all creatures
     pub fn individuals(&self) -> Vec<(Creature, Handle<GifAsset>)> {
         self.species
             .iter()
-            .map(|s| s.individuals.clone())
-            .flatten()
+            .flat_map(|s| s.individuals.clone())
             .collect()
     }
 
@@ -112,31 +206,215 @@ impl Dex {
         individuals[creature_idx].clone()
     }
 
-    /// Query the specified creature
-    /// could use newtypes here to reduce confusion.
-    pub fn get_creature(&self, ids: (usize, usize)) -> &(Creature, Handle<GifAsset>) {
+    /// return a clone of a randomly selected creature of the given aura, of any aura if
+    /// none has it
+    pub fn random_of_aura(&self, aura: Aura) -> Creature {
+        let individuals: Vec<Creature> = self
+            .individuals()
+            .into_iter()
+            .map(|(creature, _)| creature)
+            .filter(|creature| creature.aura == aura)
+            .collect();
+        individuals
+            .choose(&mut rand::rng())
+            .cloned()
+            .unwrap_or_else(|| self.random().0)
+    }
+
+    /// Query the specified creature, `None` if no creature has this id.
+    pub fn get_creature(&self, id: &CreatureId) -> Option<&(Creature, Handle<GifAsset>)> {
+        let (species, individual) = *self.creature_index.get(id)?;
+        self.species[species].individuals.get(individual)
+    }
+
+    /// Query the specified species, `None` if no species has this id.
+    pub fn get_species(&self, id: &SpeciesId) -> Option<&Species> {
+        self.species.get(*self.species_index.get(id)?)
+    }
+
+    /// Species a creature of the dex belongs to.
+    pub fn species_of(&self, creature: &Creature) -> &Species {
+        self.get_species(&creature.species_id)
+            .expect("creatures of the dex belong to a species of the dex")
+    }
+
+    /// Query the specified attack, `None` if no attack has this id.
+    pub fn get_attack(&self, id: &AttackId) -> Option<Arc<dyn Attack + Send + Sync>> {
+        self.attacks
+            .0
+            .get(*self.attack_index.get(id)?)
+            .map(Arc::clone)
+    }
+
+    /// Find a creature from its name, as written in LDtk.
+    pub fn find_creature_by_name(&self, name: &str) -> Option<&Creature> {
         self.species
-            .get(ids.0)
-            .unwrap()
-            .individuals
-            .get(ids.1)
-            .unwrap()
+            .iter()
+            .flat_map(|species| species.individuals.iter())
+            .map(|(creature, _)| creature)
+            .find(|creature| creature.name.eq_ignore_ascii_case(name))
     }
 
-    /// Return all compatible attacks for a given creature
-    pub fn filter_attacks_for_creature(&self, creature: Creature) -> Attacks {
-        let species = self.species.get(creature.species_id).unwrap();
+    /// Return all compatible attacks for a given creature at `level`: the physical attacks
+    /// of its attributes, and the ones of its element its attributes unlock.
+    /// Species with a learnset only get the attacks it lists, once they reach their level.
+    pub fn filter_attacks_for_creature(&self, creature: &Creature, level: u8) -> Attacks {
+        let species = self.species_of(creature);
         let mut attacks = self.attacks.filter_for_species(species);
-        // jsut 4 elemental attacks for now, so just pick the one.
         attacks
             .0
-            .extend(self.attacks.filter_by_elem(creature.element).0);
+            .extend(self.attacks.filter_by_elem(creature.element, species).0);
+        if let Some(learnset) = species.learnset.as_ref() {
+            attacks.0.retain(|atk| {
+                learnset
+                    .iter()
+                    .any(|learnable| learnable.attack == atk.id() && learnable.level <= level)
+            });
+        }
         attacks
     }
 
+    /// Damage multiplier of an attack against a given team member, from both elements
+    /// and physical attributes.
+    pub fn multiplier(&self, attack: &dyn Attack, defender: &TeamMember) -> f32 {
+        let species = self.species_of(defender.creature(self));
+        let elemental = match attack.element() {
+            Some(element) => element.multiplier_against(defender.element(self), &self.elements),
+            None => 1.,
+        };
+        elemental * attack.attributes_multiplier(&species.attributes)
+    }
+
+    /// Damage multiplier of a weight based attack of `attacker` against `defender`.
+    /// It is left out of the effectiveness, which only tells about elements and attributes.
+    pub fn weight_multiplier(
+        &self,
+        attack: &dyn Attack,
+        attacker: &TeamMember,
+        defender: &TeamMember,
+    ) -> f32 {
+        let attacker = self.species_of(attacker.creature(self));
+        let defender = self.species_of(defender.creature(self));
+        attack.weight_multiplier(attacker.mass_kg, defender.mass_kg)
+    }
+
     /// Return all compatible attacks for a given team member
     pub fn filter_attacks_for_team_member(&self, member: TeamMember) -> Attacks {
-        let creature = self.get_creature(member.creature_id);
-        self.filter_attacks_for_creature(creature.0.clone())
+        self.filter_attacks_for_creature(member.creature(self), member.level)
+    }
+
+    /// Attacks a creature knows when it is met at `level`: its elemental attack, then random
+    /// compatible ones up to `MAX_MOVES`.
+    pub fn initial_moves(&self, creature: &Creature, level: u8) -> Vec<AttackId> {
+        let (elemental, physical): (Vec<_>, Vec<_>) = self
+            .filter_attacks_for_creature(creature, level)
+            .into_iter()
+            .partition(|atk| atk.element().is_some());
+        let mut moves: Vec<AttackId> = elemental
+            .iter()
+            .map(|atk| atk.id())
+            .take(MAX_MOVES)
+            .collect();
+        moves.extend(
+            physical
+                .choose_multiple(&mut rand::rng(), MAX_MOVES - moves.len())
+                .map(|atk| atk.id()),
+        );
+        moves
+    }
+
+    /// Attacks a team member knows. Ids not in the dex anymore are left out.
+    pub fn moves_of(&self, member: &TeamMember) -> Attacks {
+        Attacks(
+            member
+                .moves
+                .iter()
+                .filter_map(|id| self.get_attack(id))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fire moles, which have no attribute unlocking fire attacks. Only the second one has
+    /// a learnset.
+    const MOLES: &str = r#"{
+        "name": "Mole pack",
+        "enabled": true,
+        "species": [
+            {
+                "id": "mole", "name": "mole", "mass_kg": 0.1, "height_m": 0.1,
+                "attributes": ["eyes", "paws", "claws"],
+                "stats": { "hp": 50, "attack": 50, "defense": 50, "speed": 50 },
+                "individuals": [
+                    { "id": "mole_fire", "name": "Molefire", "element": "Fire", "aura": "Wise" }
+                ]
+            },
+            {
+                "id": "learned_mole", "name": "learned_mole", "mass_kg": 0.1, "height_m": 0.1,
+                "attributes": ["eyes", "paws", "claws"],
+                "stats": { "hp": 50, "attack": 50, "defense": 50, "speed": 50 },
+                "individuals": [
+                    { "id": "learned_mole_fire", "name": "Learnedmole", "element": "Fire", "aura": "Wise" }
+                ],
+                "learnset": [{ "attack": "scratch", "level": 4 }]
+            }
+        ]
+    }"#;
+
+    fn ids(dex: &Dex, creature: &str, level: u8) -> Vec<String> {
+        let creature = &dex
+            .get_creature(&CreatureId(creature.to_string()))
+            .unwrap()
+            .0;
+        let mut ids: Vec<String> = dex
+            .filter_attacks_for_creature(creature, level)
+            .into_iter()
+            .map(|atk| atk.id().0)
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn learnsets_unlock_attacks_with_the_level() {
+        let dex = Dex::from_base_files();
+        assert_eq!(
+            ids(&dex, "flammiaou", 1),
+            ["lancebraiz", "listen"],
+            "only the fire attack of the learnset, and nothing above level 1"
+        );
+        assert_eq!(
+            ids(&dex, "flammiaou", 8),
+            ["clean", "lancebraiz", "lick", "lick_wounds", "listen"]
+        );
+        assert_eq!(ids(&dex, "mulovol", 3), ["lancegaz"]);
+        assert_eq!(ids(&dex, "mulovol", 4), ["lancegaz", "listen"]);
+    }
+
+    #[test]
+    fn elemental_attacks_need_an_unlocking_attribute() {
+        let dex = Dex::with_packs(&[("moles.pack.json", MOLES)]).unwrap();
+        assert_eq!(ids(&dex, "mole_fire", 1), ["scratch"]);
+        assert_eq!(ids(&dex, "learned_mole_fire", 1), Vec::<String>::new());
+        assert_eq!(ids(&dex, "learned_mole_fire", 4), ["scratch"]);
+
+        let locked = MOLES.replace(
+            r#"[{ "attack": "scratch", "level": 4 }]"#,
+            r#"[{ "attack": "lancebraiz" }]"#,
+        );
+        let dex = Dex::with_packs(&[("moles.pack.json", &locked)]).unwrap();
+        let PackState::Rejected(errors) = &dex.packs[0].state else {
+            panic!("learnsets should not list locked attacks");
+        };
+        assert_eq!(
+            errors,
+            &[
+                "moles.pack.json: species[1].learnset[0]: Lancebraiz needs attributes the species lacks"
+            ]
+        );
     }
 }
@@ -0,0 +1,256 @@
+use std::sync::Arc;
+
+use bevy::asset::{AssetServer, Handle};
+use bevy_easy_gif::prelude::GifAsset;
+
+use super::{
+    Attack, Creature, CreatureId, DexError, Species,
+    attacks::{MagicalAttack, PhysicalAttack},
+    data::{Entries, SpeciesData, check_attack, check_unique},
+};
+use crate::team::MAX_LEVEL;
+
+/// Name of the pack made of the files shipped with the game.
+pub const BASE_PACK: &str = "Base game";
+
+/// A pack of extra species and attacks found in `assets/creatures/packs/`.
+#[derive(Clone)]
+pub struct Pack {
+    pub name: String,
+    pub file: String,
+    pub state: PackState,
+}
+
+#[derive(Clone)]
+pub enum PackState {
+    Enabled,
+    /// Turned off by its `enabled` field.
+    Disabled,
+    /// Left out because of these errors, such as ids already used by another pack.
+    Rejected(Vec<String>),
+}
+
+/// Species and attacks gathered so far, with the path of each entry for the errors.
+#[derive(Clone, Default)]
+pub struct Contents {
+    pub species: Vec<(String, Species)>,
+    /// Path, id and name of each creature.
+    creatures: Vec<(String, String, String)>,
+    /// Path and target of each evolution.
+    evolutions: Vec<(String, CreatureId)>,
+    pub attacks: Vec<(String, Arc<dyn Attack + Send + Sync>)>,
+}
+
+impl Contents {
+    /// Add the species of `file`, telling they come from `pack`.
+    pub fn add_species(
+        &mut self,
+        file: &str,
+        species: &Entries<SpeciesData>,
+        pack: &str,
+        asset_server: &AssetServer,
+        errors: &mut Vec<DexError>,
+    ) {
+        for (path, data) in species.collect(&format!("{file}: species"), errors) {
+            data.check(&path, errors);
+            let mut species = Species::from(data);
+            species.pack = pack.to_string();
+            let individuals = data
+                .individuals
+                .collect(&format!("{path}.individuals"), errors);
+            for (path, data) in individuals {
+                for (idx, evolution) in data.evolutions.iter().enumerate() {
+                    let path = format!("{path}.evolutions[{idx}]");
+                    if !evolution.has_trigger() {
+                        errors.push(DexError::new(&path, "needs a level, an item or an aura"));
+                    }
+                    if evolution.into == data.id {
+                        errors.push(DexError::new(&path, "cannot evolve into itself"));
+                    }
+                    self.evolutions.push((path, evolution.into.clone()));
+                }
+                let creature = Creature::new(data, &species);
+                let handle: Handle<GifAsset> = asset_server.load(creature.assets_texture_path());
+                self.creatures
+                    .push((path, creature.id.0.clone(), creature.name.clone()));
+                species.individuals.push((creature, handle));
+            }
+            self.species.push((path, species));
+        }
+    }
+
+    /// Add the attacks of `file`.
+    pub fn add_attacks(
+        &mut self,
+        file: &str,
+        physical_attacks: &Entries<PhysicalAttack>,
+        magical_attacks: &Entries<MagicalAttack>,
+        errors: &mut Vec<DexError>,
+    ) {
+        for (path, pa) in physical_attacks.collect(&format!("{file}: physical_attacks"), errors) {
+            check_attack(&path, pa.damage, &pa.effects(), errors);
+            self.attacks.push((path, Arc::new(pa.clone())));
+        }
+        for (path, ma) in magical_attacks.collect(&format!("{file}: magical_attacks"), errors) {
+            check_attack(&path, ma.damage, &ma.effects(), errors);
+            self.attacks.push((path, Arc::new(ma.clone())));
+        }
+    }
+
+    /// Creatures can only evolve into creatures of the dex.
+    pub fn check_evolutions(&self, errors: &mut Vec<DexError>) {
+        for (path, into) in self.evolutions.iter() {
+            if !self.creatures.iter().any(|(_, id, _)| *id == into.0) {
+                errors.push(DexError::new(path, format!("unknown creature {into}")));
+            }
+        }
+    }
+
+    /// Learnsets list attacks of the dex that the species can learn, between the first
+    /// and the last level.
+    pub fn check_learnsets(&self, errors: &mut Vec<DexError>) {
+        for (path, species) in self.species.iter() {
+            for (idx, learnable) in species.learnset.iter().flatten().enumerate() {
+                let path = format!("{path}.learnset[{idx}]");
+                if !(1..=MAX_LEVEL).contains(&learnable.level) {
+                    errors.push(DexError::new(
+                        &format!("{path}.level"),
+                        format!("should be between 1 and {MAX_LEVEL}"),
+                    ));
+                }
+                match self
+                    .attacks
+                    .iter()
+                    .find(|(_, attack)| attack.id() == learnable.attack)
+                {
+                    None => errors.push(DexError::new(
+                        &path,
+                        format!("unknown attack {}", learnable.attack),
+                    )),
+                    Some((_, attack)) if !attack.is_learnable_with(&species.attributes) => errors
+                        .push(DexError::new(
+                            &path,
+                            format!("{} needs attributes the species lacks", attack.name()),
+                        )),
+                    Some(_) => {}
+                }
+            }
+        }
+    }
+
+    /// Ids and names must be unique, across all packs.
+    pub fn check_unique(&self, errors: &mut Vec<DexError>) {
+        check_unique(
+            "species id",
+            self.species
+                .iter()
+                .map(|(path, sp)| (path.as_str(), sp.id.0.as_str())),
+            errors,
+        );
+        check_unique(
+            "species name",
+            self.species
+                .iter()
+                .map(|(path, sp)| (path.as_str(), sp.name.as_str())),
+            errors,
+        );
+        check_unique(
+            "creature id",
+            self.creatures
+                .iter()
+                .map(|(path, id, _)| (path.as_str(), id.as_str())),
+            errors,
+        );
+        check_unique(
+            "creature name",
+            self.creatures
+                .iter()
+                .map(|(path, _, name)| (path.as_str(), name.as_str())),
+            errors,
+        );
+        let attack_names: Vec<(&str, String, String)> = self
+            .attacks
+            .iter()
+            .map(|(path, attack)| (path.as_str(), attack.id().0, attack.name()))
+            .collect();
+        check_unique(
+            "attack id",
+            attack_names
+                .iter()
+                .map(|(path, id, _)| (*path, id.as_str())),
+            errors,
+        );
+        check_unique(
+            "attack name",
+            attack_names
+                .iter()
+                .map(|(path, _, name)| (*path, name.as_str())),
+            errors,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::Dex;
+
+    const EXAMPLE: &str = include_str!("../../assets/creatures/packs/example.pack.json");
+
+    fn lizard() -> CreatureId {
+        CreatureId(String::from("example_lizard_fire"))
+    }
+
+    #[test]
+    fn disabled_packs_are_left_out() {
+        let dex = Dex::with_packs(&[("example.pack.json", EXAMPLE)]).unwrap();
+        assert!(matches!(
+            dex.packs[..],
+            [Pack {
+                state: PackState::Disabled,
+                ..
+            }]
+        ));
+        assert!(dex.get_creature(&lizard()).is_none());
+    }
+
+    #[test]
+    fn enabled_packs_add_their_creatures() {
+        let enabled = EXAMPLE.replace(r#""enabled": false"#, r#""enabled": true"#);
+        let dex = Dex::with_packs(&[("example.pack.json", &enabled)]).unwrap();
+        assert!(matches!(
+            dex.packs[..],
+            [Pack {
+                state: PackState::Enabled,
+                ..
+            }]
+        ));
+        assert!(dex.get_creature(&lizard()).is_some());
+    }
+
+    #[test]
+    fn packs_reusing_an_id_are_rejected_alone() {
+        let enabled = EXAMPLE.replace(r#""enabled": false"#, r#""enabled": true"#);
+        let copy = enabled.replace("Example pack", "Copy pack");
+        let broken = r#"{ "name": "Broken pack", "species": 3 }"#;
+        let dex = Dex::with_packs(&[
+            ("a.pack.json", &enabled),
+            ("b.pack.json", &copy),
+            ("c.pack.json", broken),
+        ])
+        .unwrap();
+        let states: Vec<&PackState> = dex.packs.iter().map(|pack| &pack.state).collect();
+        assert!(matches!(states[0], PackState::Enabled));
+        let PackState::Rejected(errors) = states[1] else {
+            panic!("the copy should be rejected");
+        };
+        assert!(
+            errors
+                .iter()
+                .any(|error| error.contains("creature id example_lizard_fire is already used")),
+            "{errors:?}"
+        );
+        assert!(matches!(states[2], PackState::Rejected(_)));
+        assert!(dex.get_creature(&lizard()).is_some());
+    }
+}
@@ -1,13 +1,21 @@
+use std::str::FromStr;
+
 use bevy::{asset::Handle, ecs::resource::Resource};
 use bevy_easy_gif::prelude::GifAsset;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
 
-use super::element::Element;
+use super::{
+    aura::Aura,
+    data::{CreatureData, SpeciesData},
+    element::Element,
+    evolution::Evolution,
+    ids::{AttackId, CreatureId, SpeciesId},
+};
 
 /// Physical attributes that a creature can have
 /// It determines physical attacks and damage multipliers?
 #[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(try_from = "String")]
 pub enum Attribute {
     Ears,
     Tail,
@@ -20,15 +28,15 @@ pub enum Attribute {
     Beak,
     Claws,
     Tongue,
+    Wool,
+    Scales,
 }
 
-impl Attribute {
-    pub fn from_value(value: &Value) -> Self {
-        let attr_str = value
-            .as_str()
-            .expect("attribute should be a string")
-            .to_lowercase();
-        match attr_str.as_str() {
+impl FromStr for Attribute {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, String> {
+        Ok(match value.to_ascii_lowercase().as_str() {
             "ears" => Attribute::Ears,
             "tail" => Attribute::Tail,
             "eyes" => Attribute::Eyes,
@@ -40,12 +48,23 @@ impl Attribute {
             "beak" => Attribute::Beak,
             "claws" => Attribute::Claws,
             "tongue" => Attribute::Tongue,
-            x => panic!("Unknown attribute type {x}"),
-        }
+            "wool" => Attribute::Wool,
+            "scales" => Attribute::Scales,
+            _ => return Err(format!("unknown attribute {value}")),
+        })
+    }
+}
+
+impl TryFrom<String> for Attribute {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, String> {
+        value.parse()
     }
 }
 
 #[derive(Clone, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
 /// List of stats for a species or a creature. They act as "base stats", and are altered
 /// in fight by active effects.
 pub struct Stats {
@@ -53,35 +72,31 @@ pub struct Stats {
     pub attack: u8,
     pub defense: u8,
     pub speed: u8,
-    /// this guy is a %
+    /// this guy is a %, none by default
+    #[serde(default)]
     pub dodge: u8,
-    /// this guy is a %
+    /// this guy is a %, always hits by default
+    #[serde(default = "full_accuracy")]
     pub accuracy: u8,
 }
 
+fn full_accuracy() -> u8 {
+    100
+}
+
+/// Creatures smaller than this dodge better, in m.
+const DODGE_REFERENCE_HEIGHT: f32 = 1.;
+/// Dodge gained each time the height is halved below the reference, in %.
+const DODGE_PER_HALVING: f32 = 5.;
+/// Most dodge a creature gets from its size, in %.
+const MAX_SIZE_DODGE: f32 = 20.;
+
 impl Stats {
-    pub fn from_value(value: &Value) -> Self {
-        let hp = value["stats"]["hp"]
-            .as_u64()
-            .expect("hp should be a positive integer") as u8;
-        let attack = value["stats"]["attack"]
-            .as_u64()
-            .expect("attack should be a positive integer") as u8;
-        let defense = value["stats"]["defense"]
-            .as_u64()
-            .expect("defense should be a positive integer") as u8;
-        let speed = value["stats"]["speed"]
-            .as_u64()
-            .expect("speed should be a positive integer") as u8;
-        Stats {
-            hp,
-            attack,
-            defense,
-            speed,
-            // default for all
-            dodge: 0,
-            accuracy: 100,
-        }
+    /// Small species are harder to hit: they dodge more the smaller they are.
+    pub fn with_height(&self, height_m: f32) -> Self {
+        let mut stats = self.clone();
+        stats.dodge = stats.dodge.saturating_add(size_dodge(height_m)).min(100);
+        stats
     }
 
     /// Slightly update stats based on an element (used for an individual)
@@ -93,12 +108,12 @@ impl Stats {
                 stats.speed = (stats.defense as f32 * 0.95).round() as u8;
             }
             Element::Water => {
-                stats.dodge += 5;
+                stats.dodge = stats.dodge.saturating_add(5);
                 stats.attack = (stats.defense as f32 * 0.95).round() as u8;
             }
             Element::Air => {
                 stats.attack = (stats.speed as f32 * 1.05).round() as u8;
-                stats.accuracy -= 5;
+                stats.accuracy = stats.accuracy.saturating_sub(5);
             }
             Element::Earth => {
                 stats.defense = (stats.speed as f32 * 1.05).round() as u8;
@@ -147,27 +162,27 @@ impl Iterator for StatsIntoIterator {
 
 #[derive(Clone, Serialize, Deserialize, Resource, Default)]
 pub struct Creature {
+    pub id: CreatureId,
     pub name: String,
     pub element: Element,
-    pub species_id: usize,
+    pub aura: Aura,
+    pub species_id: SpeciesId,
     pub stats: Stats,
+    /// What it can turn into.
+    #[serde(default)]
+    pub evolutions: Vec<Evolution>,
 }
 
 impl Creature {
-    pub fn from_value(value: &Value, species_id: usize, species_stats: &Stats) -> Self {
-        let name = value["name"]
-            .as_str()
-            .expect("creature name should be a string")
-            .to_string();
-        let element = value["element"]
-            .as_str()
-            .expect("creature element should be a string")
-            .into();
+    pub fn new(data: &CreatureData, species: &Species) -> Self {
         Creature {
-            name,
-            stats: species_stats.with_element(element),
-            element,
-            species_id,
+            id: data.id.clone(),
+            name: data.name.clone(),
+            stats: species.stats.with_element(data.element),
+            element: data.element,
+            aura: data.aura,
+            species_id: species.id.clone(),
+            evolutions: data.evolutions.clone(),
         }
     }
 
@@ -180,46 +195,59 @@ impl Creature {
     }
 }
 
-///
+/// Dodge bonus of a creature of this height, in %.
+pub fn size_dodge(height_m: f32) -> u8 {
+    let halvings = (DODGE_REFERENCE_HEIGHT / height_m).log2().max(0.);
+    (halvings * DODGE_PER_HALVING).min(MAX_SIZE_DODGE).round() as u8
+}
+
+/// A species groups creatures sharing the same body, declined by elements.
 #[derive(Clone)]
-#[allow(dead_code)]
 pub struct Species {
+    pub id: SpeciesId,
     pub name: String,
-    mass: f32,   // kg
-    height: f32, // m
+    /// Heavier species hit harder with weight based attacks.
+    pub mass_kg: f32,
+    /// Smaller species dodge better, see `Stats::with_height`.
+    pub height_m: f32,
     pub attributes: Vec<Attribute>,
     pub stats: Stats,
     pub individuals: Vec<(Creature, Handle<GifAsset>)>,
+    /// Name of the pack the species comes from.
+    pub pack: String,
+    /// Attacks it learns and from which level. Without one, it learns every compatible
+    /// attack from the start.
+    pub learnset: Option<Vec<Learnable>>,
 }
 
-impl Species {
-    pub fn from_value(value: &Value) -> Self {
-        let name = value["name"]
-            .as_str()
-            .expect("species name should be a string")
-            .to_string();
-        let mass = value["mass_kg"]
-            .as_f64()
-            .expect("mass_kg should be a number") as f32;
-        let height = value["height_m"]
-            .as_f64()
-            .expect("height_m should be a number") as f32;
-        let mut attributes = Vec::new();
-        for attr in value["attributes"]
-            .as_array()
-            .expect("attributes should be an array")
-        {
-            let attribute = Attribute::from_value(attr);
-            attributes.push(attribute);
-        }
-        let stats = Stats::from_value(value);
+/// An attack of a learnset.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Learnable {
+    pub attack: AttackId,
+    /// Level from which it can be learnt.
+    #[serde(default = "first_level")]
+    pub level: u8,
+}
+
+fn first_level() -> u8 {
+    1
+}
+
+/// Individuals are left out, they need the species to be built.
+/// The pack is left to the caller too.
+impl From<&SpeciesData> for Species {
+    fn from(data: &SpeciesData) -> Self {
         Species {
-            name,
-            mass,
-            height,
-            attributes,
-            stats,
+            id: data.id.clone(),
+            name: data.name.clone(),
+            mass_kg: data.mass_kg,
+            height_m: data.height_m,
+            attributes: data.attributes.clone(),
+            stats: data.stats.with_height(data.height_m),
             individuals: vec![],
+            pack: String::new(),
+            learnset: data.learnset.clone(),
         }
     }
 }
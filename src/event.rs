@@ -1,12 +1,19 @@
 use bevy::prelude::*;
-use bevy_ecs_ldtk::{GridCoords, LevelSelection};
+use bevy_ecs_ldtk::{
+    GridCoords, LdtkProjectHandle, LevelSelection,
+    assets::{LdtkProject, LevelMetadataAccessor},
+};
 
 use crate::{
     AppState,
-    dex::{Creature, Dex},
+    dex::{AttackId, Creature, Dex},
+    fight::Trainer,
+    items::Inventory,
     player::Player,
     save::Save,
-    team::Team,
+    taming::{PlayerAura, Rings},
+    team::{AwayMembers, Team},
+    world::RestPoint,
 };
 
 // `Save`-related
@@ -19,9 +26,18 @@ impl Plugin for EventsPlugin {
             .add_event::<LoadSaveEvent>()
             .add_event::<MoveInBushEvent>()
             .add_event::<WildEncounterEvent>()
+            .add_event::<TrainerEncounterEvent>()
+            .add_event::<RelearnMoveEvent>()
             .add_systems(
                 Update,
-                (new_save, spawn_wild_encounter, wild_encounter).run_if(in_state(AppState::InGame)),
+                (
+                    new_save,
+                    spawn_wild_encounter,
+                    wild_encounter,
+                    trainer_encounter,
+                    relearn_move,
+                )
+                    .run_if(in_state(AppState::InGame)),
             );
     }
 }
@@ -32,19 +48,45 @@ pub struct NewSaveEvent;
 
 /// Gather what matters and save it all.
 /// Might be a better way to do so, is that Events ? <-- TODO investigate
+#[allow(clippy::too_many_arguments)]
 pub fn new_save(
     mut events: EventReader<NewSaveEvent>,
     player_q: Query<&GridCoords, With<Player>>,
     level_res: Res<LevelSelection>,
     team: Res<Team>,
+    away: Res<AwayMembers>,
+    rest_point: Option<Res<RestPoint>>,
+    rings: Res<Rings>,
+    aura: Res<PlayerAura>,
+    inventory: Res<Inventory>,
+    ldtk_project_entities: Query<&LdtkProjectHandle>,
+    ldtk_project_assets: Res<Assets<LdtkProject>>,
 ) {
     for _ in events.read() {
-        let level_id = match *level_res {
+        let level_id = match &*level_res {
             LevelSelection::Indices(x) => x.level,
+            // after travelling between levels
+            LevelSelection::Iid(iid) => {
+                ldtk_project_assets
+                    .get(ldtk_project_entities.single().unwrap())
+                    .and_then(|project| project.get_level_metadata_by_iid(iid.get()))
+                    .expect("selected level should exist in project")
+                    .indices()
+                    .level
+            }
             _ => todo!("not supported"),
         };
         let coords = player_q.single().unwrap();
-        Save::new(level_id as i32, *coords, team.clone());
+        Save::new(
+            level_id as i32,
+            *coords,
+            team.clone(),
+            away.clone(),
+            rest_point.as_deref().cloned(),
+            rings.clone(),
+            aura.0,
+            inventory.clone(),
+        );
     }
 }
 
@@ -63,8 +105,13 @@ fn spawn_wild_encounter(
     mut move_in_bush_reader: EventReader<MoveInBushEvent>,
     mut wild_encounter_writer: EventWriter<WildEncounterEvent>,
     dex: Res<Dex>,
+    team: Res<Team>,
 ) {
     for _ in move_in_bush_reader.read() {
+        if !team.can_fight() {
+            // nothing to fight with, wild creatures leave us alone
+            continue;
+        }
         let mut rng = rand::rng();
         let nbr = rand::Rng::random::<u8>(&mut rng);
         if nbr < 64 {
@@ -87,6 +134,56 @@ fn wild_encounter(
 ) {
     for event in wild_encounter_reader.read() {
         commands.insert_resource(event.0.clone());
+        commands.remove_resource::<Trainer>();
+        next_state.set(AppState::InFight);
+    }
+}
+
+// "Trainer encounter"-related events
+
+#[derive(Event)]
+/// Trigger before a fight against a trainer.
+pub struct TrainerEncounterEvent(pub Trainer);
+
+/// Prepare the data for a fight against a trainer.
+fn trainer_encounter(
+    mut trainer_encounter_reader: EventReader<TrainerEncounterEvent>,
+    team: Res<Team>,
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    for event in trainer_encounter_reader.read() {
+        if !team.can_fight() {
+            println!("{}: come back when your creatures can fight!", event.0.name);
+            continue;
+        }
+        commands.insert_resource(event.0.clone());
+        commands.remove_resource::<Creature>();
         next_state.set(AppState::InFight);
     }
 }
+
+// "Team"-related events
+
+#[derive(Event)]
+/// Trigger when the player teaches a team member an attack of its compatible pool.
+pub struct RelearnMoveEvent {
+    /// Index of the member in the `Team`.
+    pub member: usize,
+    pub attack: AttackId,
+    /// Index of the attack to forget, if the member already knows as many as it can.
+    pub forget: Option<usize>,
+}
+
+/// Teach the attack, if the member can learn it.
+fn relearn_move(
+    mut relearn_move_reader: EventReader<RelearnMoveEvent>,
+    mut team: ResMut<Team>,
+    dex: Res<Dex>,
+) {
+    for event in relearn_move_reader.read() {
+        if let Some(member) = team.0.get_mut(event.member) {
+            member.relearn(&event.attack, event.forget, &dex);
+        }
+    }
+}
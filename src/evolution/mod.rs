@@ -0,0 +1,57 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+mod systems;
+
+use systems::*;
+
+use crate::{AppState, dex::CreatureId};
+
+/// A member of the team ready to evolve, waiting for the player to agree.
+#[derive(Clone)]
+pub struct PendingEvolution {
+    /// Index of the member in the `Team`.
+    pub member: usize,
+    /// Creature it was when the evolution was found, in case the team changed since.
+    pub from: CreatureId,
+    pub into: CreatureId,
+    /// Item spent once the evolution happens.
+    pub item: Option<String>,
+}
+
+/// Evolutions asked to the player, one at a time, in order.
+#[derive(Resource, Default)]
+pub struct PendingEvolutions(pub VecDeque<PendingEvolution>);
+
+impl PendingEvolutions {
+    /// Ask for an evolution, unless the member already waits for one.
+    pub fn ask(&mut self, evolution: PendingEvolution) {
+        if !self
+            .0
+            .iter()
+            .any(|pending| pending.member == evolution.member)
+        {
+            self.0.push_back(evolution);
+        }
+    }
+}
+
+#[derive(Event)]
+/// Trigger when the player answers the first pending evolution.
+pub struct EvolveEvent {
+    /// Whether the player let the member evolve, rather than cancelling it.
+    pub confirm: bool,
+}
+
+pub struct EvolutionPlugin;
+
+impl Plugin for EvolutionPlugin {
+    fn build(&self, app: &mut bevy::app::App) {
+        app.init_resource::<PendingEvolutions>()
+            .add_event::<EvolveEvent>()
+            .add_systems(Update, check_evolutions.run_if(in_state(AppState::InFight)))
+            .add_systems(Update, evolve.run_if(in_state(AppState::InGame)))
+            .add_systems(OnEnter(AppState::MainMenu), forget_pending_evolutions);
+    }
+}
@@ -0,0 +1,82 @@
+use bevy::prelude::*;
+
+use crate::{
+    dex::Dex,
+    evolution::{EvolveEvent, PendingEvolution, PendingEvolutions},
+    fight::{Battle, BattleEvent},
+    items::Inventory,
+    taming::PlayerAura,
+    team::Team,
+};
+
+/// Members leveling up during a fight may evolve once it is over.
+pub fn check_evolutions(
+    mut events: EventReader<BattleEvent>,
+    mut pending: ResMut<PendingEvolutions>,
+    battle: Option<Res<Battle>>,
+    team: Res<Team>,
+    aura: Res<PlayerAura>,
+    dex: Res<Dex>,
+) {
+    let Some(battle) = battle else {
+        return;
+    };
+    for event in events.read() {
+        let BattleEvent::LeveledUp { target, .. } = event else {
+            continue;
+        };
+        // the team is up to date, the battle wrote its XP back
+        let Some(slot) = battle.combatants[*target].team_slot else {
+            continue;
+        };
+        let Some(member) = team.0.get(slot) else {
+            continue;
+        };
+        if let Some(into) = member.evolution(aura.0, None, &dex) {
+            pending.ask(PendingEvolution {
+                member: slot,
+                from: member.creature_id.clone(),
+                into,
+                item: None,
+            });
+        }
+    }
+}
+
+/// Evolve the member of the first pending evolution, or forget about it if the player
+/// cancelled it. Cancelled evolutions are asked again on the next level up.
+pub fn evolve(
+    mut events: EventReader<EvolveEvent>,
+    mut pending: ResMut<PendingEvolutions>,
+    mut team: ResMut<Team>,
+    mut inventory: ResMut<Inventory>,
+    dex: Res<Dex>,
+) {
+    for event in events.read() {
+        let Some(evolution) = pending.0.pop_front() else {
+            continue;
+        };
+        if !event.confirm {
+            continue;
+        }
+        let Some(member) = team.0.get_mut(evolution.member) else {
+            continue;
+        };
+        if member.creature_id != evolution.from {
+            continue;
+        }
+        if let Some(item) = evolution.item.as_ref()
+            && !inventory.take(item)
+        {
+            continue;
+        }
+        let name = member.name(&dex);
+        member.evolve(&evolution.into, &dex);
+        info!("{name} evolved into {}", member.creature(&dex).name);
+    }
+}
+
+/// Pending evolutions belong to the game being left.
+pub fn forget_pending_evolutions(mut pending: ResMut<PendingEvolutions>) {
+    pending.0.clear();
+}
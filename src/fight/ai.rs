@@ -0,0 +1,249 @@
+use std::{str::FromStr, sync::Arc};
+
+use rand::seq::IndexedRandom;
+
+use crate::{
+    dex::{Attack, Dex, Effect},
+    fight::{Battle, BattleAction},
+};
+
+/// Below this share of its max HP, a cautious foe takes care of itself.
+const CAUTIOUS_HP_RATIO: f32 = 1. / 3.;
+
+/// Brain of the foes: decide what a combatant does this round.
+pub trait Ai {
+    /// Action of `source`, `None` if it cannot do anything.
+    fn choose(&self, battle: &Battle, source: usize, dex: &Dex) -> Option<BattleAction>;
+}
+
+/// Which `Ai` drives the foes. Trainers read it from their LDtk entity,
+/// wild creatures from the level they live in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AiKind {
+    #[default]
+    Random,
+    Greedy,
+    Cautious,
+}
+
+impl AiKind {
+    pub fn ai(&self) -> Box<dyn Ai + Send + Sync> {
+        match self {
+            AiKind::Random => Box::new(RandomAi),
+            AiKind::Greedy => Box::new(GreedyAi),
+            AiKind::Cautious => Box::new(CautiousAi),
+        }
+    }
+}
+
+impl FromStr for AiKind {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, String> {
+        Ok(match value {
+            "Random" => Self::Random,
+            "Greedy" => Self::Greedy,
+            "Cautious" => Self::Cautious,
+            _ => return Err(format!("unknown ai {value}")),
+        })
+    }
+}
+
+/// Any attack on any target.
+pub struct RandomAi;
+
+/// The attack and target dealing the most damage to the other side, friendly fire deducted.
+pub struct GreedyAi;
+
+/// Like `GreedyAi`, but heal, or else cleanse or buff itself first when its HP run low.
+pub struct CautiousAi;
+
+type Attacks = Vec<Arc<dyn Attack + Send + Sync>>;
+
+fn attacks_of(battle: &Battle, source: usize, dex: &Dex) -> Attacks {
+    dex.moves_of(&battle.combatants[source].member).0
+}
+
+/// Targets the attack can be aimed at, the source itself when there is no choice to make.
+fn aims(battle: &Battle, source: usize, attack: &dyn Attack) -> Vec<usize> {
+    let candidates = battle.candidates(source, attack.target_type());
+    if candidates.is_empty() {
+        vec![source]
+    } else {
+        candidates
+    }
+}
+
+impl Ai for RandomAi {
+    fn choose(&self, battle: &Battle, source: usize, dex: &Dex) -> Option<BattleAction> {
+        let mut rng = rand::rng();
+        let attacks = attacks_of(battle, source, dex);
+        let attack = attacks.choose(&mut rng)?;
+        let target = *aims(battle, source, attack.as_ref()).choose(&mut rng)?;
+        Some(BattleAction::Attack {
+            source,
+            attack: Arc::clone(attack),
+            target,
+        })
+    }
+}
+
+impl GreedyAi {
+    /// Expected damage dealt to the other side, minus the damage dealt to its own side.
+    fn score(battle: &Battle, source: usize, attack: &dyn Attack, aim: usize, dex: &Dex) -> f32 {
+        let Some(power) = attack.damage() else {
+            return 0.;
+        };
+        let side = battle.combatants[source].side;
+        battle
+            .targets(source, attack.target_type(), aim)
+            .into_iter()
+            .map(|target| {
+                let multiplier = dex.multiplier(attack, &battle.combatants[target].member)
+                    * dex.weight_multiplier(
+                        attack,
+                        &battle.combatants[source].member,
+                        &battle.combatants[target].member,
+                    );
+                let damage = battle.damage(source, target, power, multiplier, dex) as f32;
+                if battle.combatants[target].side == side {
+                    -damage
+                } else {
+                    damage
+                }
+            })
+            .sum()
+    }
+}
+
+impl Ai for GreedyAi {
+    fn choose(&self, battle: &Battle, source: usize, dex: &Dex) -> Option<BattleAction> {
+        let attacks = attacks_of(battle, source, dex);
+        let best = attacks
+            .iter()
+            .flat_map(|attack| {
+                aims(battle, source, attack.as_ref())
+                    .into_iter()
+                    .map(move |aim| (attack, aim))
+            })
+            .map(|(attack, aim)| {
+                let score = Self::score(battle, source, attack.as_ref(), aim, dex);
+                (attack, aim, score)
+            })
+            .max_by(|a, b| a.2.total_cmp(&b.2));
+        match best {
+            Some((attack, target, score)) if score > 0. => Some(BattleAction::Attack {
+                source,
+                attack: Arc::clone(attack),
+                target,
+            }),
+            // nothing hurts the other side, better do anything than nothing
+            _ => RandomAi.choose(battle, source, dex),
+        }
+    }
+}
+
+impl CautiousAi {
+    /// How much the attack takes care of `source` when aimed at `aim`, without hurting it:
+    /// healing comes first, then cleansing or buffing, 0 if it does not help.
+    fn care(battle: &Battle, source: usize, attack: &dyn Attack, aim: usize) -> u8 {
+        let combatant = &battle.combatants[source];
+        if attack.damage().is_some()
+            || !battle
+                .targets(source, attack.target_type(), aim)
+                .contains(&source)
+        {
+            return 0;
+        }
+        attack
+            .effects()
+            .iter()
+            .map(|effect| match effect {
+                Effect::Heal { .. } => 2,
+                Effect::Cleanse if combatant.effects.status().is_some() => 1,
+                Effect::StatsChange { stages, .. } if *stages > 0 => 1,
+                Effect::Cleanse | Effect::StatsChange { .. } | Effect::StatusChange { .. } => 0,
+            })
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl Ai for CautiousAi {
+    fn choose(&self, battle: &Battle, source: usize, dex: &Dex) -> Option<BattleAction> {
+        let member = &battle.combatants[source].member;
+        if (member.hp as f32) < member.max_hp(dex) as f32 * CAUTIOUS_HP_RATIO {
+            let attacks = attacks_of(battle, source, dex);
+            let care = attacks
+                .iter()
+                .flat_map(|attack| {
+                    aims(battle, source, attack.as_ref())
+                        .into_iter()
+                        .map(move |aim| (attack, aim))
+                })
+                .map(|(attack, aim)| {
+                    (
+                        attack,
+                        aim,
+                        Self::care(battle, source, attack.as_ref(), aim),
+                    )
+                })
+                .filter(|(_, _, care)| *care > 0)
+                .max_by_key(|(_, _, care)| *care);
+            if let Some((attack, target, _)) = care {
+                return Some(BattleAction::Attack {
+                    source,
+                    attack: Arc::clone(attack),
+                    target,
+                });
+            }
+        }
+        GreedyAi.choose(battle, source, dex)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        dex::{AttackId, CreatureId},
+        team::{Team, TeamMember},
+    };
+
+    fn member(id: &str, moves: &[&str], dex: &Dex) -> TeamMember {
+        let creature = &dex.get_creature(&CreatureId(id.to_string())).unwrap().0;
+        let mut member = TeamMember::new(creature, dex);
+        member.moves = moves.iter().map(|id| AttackId(id.to_string())).collect();
+        member
+    }
+
+    fn chosen_attack(action: Option<BattleAction>) -> String {
+        match action {
+            Some(BattleAction::Attack { attack, .. }) => attack.id().0,
+            _ => panic!("no attack chosen"),
+        }
+    }
+
+    #[test]
+    fn ai_kinds_are_read_by_name() {
+        assert_eq!("Cautious".parse(), Ok(AiKind::Cautious));
+        assert!("cautius".parse::<AiKind>().is_err());
+    }
+
+    #[test]
+    fn cautious_foes_heal_when_low() {
+        let dex = Dex::from_base_files();
+        let team = Team(vec![member("ratcendre", &["lancegaz"], &dex)]);
+        let foe = member("flammiaou", &["lancegaz", "listen", "lick_wounds"], &dex);
+        let mut battle = Battle::new(&team, &[0], vec![foe], 1, AiKind::Cautious);
+        assert_eq!(
+            chosen_attack(CautiousAi.choose(&battle, 1, &dex)),
+            "lancegaz"
+        );
+        battle.combatants[1].member.hp = 1;
+        assert_eq!(
+            chosen_attack(CautiousAi.choose(&battle, 1, &dex)),
+            "lick_wounds"
+        );
+    }
+}
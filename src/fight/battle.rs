@@ -0,0 +1,1096 @@
+use std::sync::Arc;
+
+use bevy::ecs::{event::Event, resource::Resource};
+use rand::Rng;
+
+use crate::{
+    dex::{Attack, Dex, Effect, Effectiveness, Stat, Stats, Status, Target},
+    fight::{ai::AiKind, effects::ActiveEffects},
+    items::Item,
+    team::{Team, TeamMember},
+};
+
+/// Damage multiplier of a critical hit.
+const CRIT_MULTIPLIER: f32 = 1.5;
+/// Each failed attempt to flee makes the next one this much more likely, in percent.
+const FLEE_BONUS_PER_ATTEMPT: u32 = 25;
+
+/// Which side of the field a combatant fights for.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Side {
+    Player,
+    Foe,
+}
+
+impl Side {
+    pub fn opponent(&self) -> Self {
+        match self {
+            Self::Player => Self::Foe,
+            Self::Foe => Self::Player,
+        }
+    }
+}
+
+/// A creature taking part in a fight.
+/// It owns a copy of the `TeamMember`, which is written back to the `Team` when needed.
+#[derive(Clone)]
+pub struct Combatant {
+    pub side: Side,
+    pub member: TeamMember,
+    /// Index of the member in the player's `Team`, `None` for foes.
+    pub team_slot: Option<usize>,
+    pub effects: ActiveEffects,
+    /// Whether it is on the field, rather than waiting for its turn to come.
+    pub active: bool,
+    /// Whether it has been on the field at some point of the fight.
+    pub entered: bool,
+}
+
+impl Combatant {
+    fn new(side: Side, member: TeamMember, team_slot: Option<usize>) -> Self {
+        Combatant {
+            side,
+            member,
+            team_slot,
+            effects: ActiveEffects::default(),
+            active: false,
+            entered: false,
+        }
+    }
+
+    fn enter(&mut self) {
+        self.active = true;
+        self.entered = true;
+    }
+
+    /// Leave the field. Effects only last while on the field.
+    fn leave(&mut self) {
+        self.active = false;
+        self.effects = ActiveEffects::default();
+    }
+
+    pub fn is_ko(&self) -> bool {
+        self.member.is_ko()
+    }
+
+    /// Whether it is on the field and able to fight.
+    pub fn is_fighting(&self) -> bool {
+        self.active && !self.is_ko()
+    }
+
+    /// Stats of the combatant, altered by its active effects.
+    pub fn stats(&self, dex: &Dex) -> Stats {
+        self.effects.apply_to(self.member.stats(dex))
+    }
+
+    /// Lose HP, and return the event to send if it faints.
+    fn hurt(&mut self, target: usize, amount: u8) -> Option<BattleEvent> {
+        self.member.hp = self.member.hp.saturating_sub(amount);
+        self.is_ko().then_some(BattleEvent::Fainted { target })
+    }
+}
+
+/// Something a combatant does during its turn.
+#[derive(Clone)]
+pub enum BattleAction {
+    Attack {
+        source: usize,
+        attack: Arc<dyn Attack + Send + Sync>,
+        target: usize,
+    },
+    /// `source` leaves the field, `target` of the same side takes its place.
+    Switch { source: usize, target: usize },
+    /// The player uses an item on `target`, which costs the turn of `source`.
+    UseItem {
+        source: usize,
+        item: Item,
+        target: usize,
+    },
+}
+
+impl BattleAction {
+    /// Combatant doing the action.
+    pub fn source(&self) -> usize {
+        match self {
+            Self::Attack { source, .. }
+            | Self::Switch { source, .. }
+            | Self::UseItem { source, .. } => *source,
+        }
+    }
+
+    /// Actions with a lower priority happen first in the round, whatever the speed.
+    fn priority(&self) -> u8 {
+        match self {
+            Self::Switch { .. } => 0,
+            Self::UseItem { .. } => 1,
+            Self::Attack { .. } => 2,
+        }
+    }
+}
+
+/// Everything that happens in a fight. The engine emits them, the UI merely renders them.
+#[derive(Event, Clone, Debug)]
+pub enum BattleEvent {
+    /// `source` used the attack named `attack`.
+    AttackUsed { source: usize, attack: String },
+    /// `target` enters the field.
+    SentOut { target: usize },
+    /// `source` left the field.
+    Withdrawn { source: usize },
+    /// The item named `item` was used on `target`.
+    ItemUsed { target: usize, item: String },
+    /// The item named `item` had no effect on `target`, and was not spent.
+    ItemWasted { target: usize, item: String },
+    /// `target` got `amount` HP back.
+    Healed { target: usize, amount: u8 },
+    /// `source` missed `target`.
+    Missed { source: usize, target: usize },
+    /// The attack against `target` was a critical hit.
+    CriticalHit { target: usize },
+    /// The attack against `target` was not neutral.
+    Effective {
+        target: usize,
+        effectiveness: Effectiveness,
+    },
+    /// `target` lost `amount` HP.
+    Damaged { target: usize, amount: u8 },
+    /// A stat of `target` changed by `stages`.
+    StatChanged {
+        target: usize,
+        stat: Stat,
+        stages: i8,
+    },
+    /// A stat of `target` is back to normal.
+    StatRestored { target: usize, stat: Stat },
+    /// `target` now suffers from `status`.
+    StatusInflicted { target: usize, status: Status },
+    /// `target` lost `amount` HP because of its status.
+    StatusHurt {
+        target: usize,
+        status: Status,
+        amount: u8,
+    },
+    /// `source` could not act because of its status.
+    Immobilized { source: usize, status: Status },
+    /// `target` does not suffer from `status` anymore.
+    StatusCured { target: usize, status: Status },
+    /// Lowered stats and status of `target` were removed.
+    Cleansed { target: usize },
+    /// `target` has no HP left.
+    Fainted { target: usize },
+    /// The fight is over, `winner` being the side still standing.
+    Ended { winner: Side },
+    /// `target` earned `amount` XP.
+    XpGained { target: usize, amount: u32 },
+    /// `target` reached `level`.
+    LeveledUp { target: usize, level: u8 },
+    /// The player ran away from the fight.
+    Fled,
+    /// The player tried to run away, but the foes caught up.
+    FleeFailed,
+}
+
+/// State of the ongoing fight.
+#[derive(Resource, Clone)]
+pub struct Battle {
+    pub combatants: Vec<Combatant>,
+    /// How many combatants of each side are on the field at once.
+    pub slots: usize,
+    /// Actions chosen for the current round, played when entering `FightState::Action`.
+    pub actions: Vec<BattleAction>,
+    /// How the foes choose their actions.
+    pub ai: AiKind,
+    /// Failed attempts of the player to flee.
+    pub flee_attempts: u32,
+}
+
+impl Battle {
+    /// Build a fight between the selected members of the team and the given foes,
+    /// with `slots` combatants of each side on the field at once. Foes are driven by `ai`.
+    pub fn new(
+        team: &Team,
+        selection: &[usize],
+        foes: Vec<TeamMember>,
+        slots: usize,
+        ai: AiKind,
+    ) -> Self {
+        let mut combatants: Vec<Combatant> = selection
+            .iter()
+            .filter_map(|slot| {
+                let member = team.0.get(*slot)?.clone();
+                Some(Combatant::new(Side::Player, member, Some(*slot)))
+            })
+            .collect();
+        combatants.extend(
+            foes.into_iter()
+                .map(|member| Combatant::new(Side::Foe, member, None)),
+        );
+        let mut battle = Battle {
+            combatants,
+            slots,
+            actions: Vec::new(),
+            ai,
+            flee_attempts: 0,
+        };
+        battle.fill_slots(Side::Player);
+        battle.fill_slots(Side::Foe);
+        battle
+    }
+
+    /// Indexes of the combatants of the given side on the field and able to fight.
+    pub fn actives(&self, side: Side) -> Vec<usize> {
+        (0..self.combatants.len())
+            .filter(|idx| {
+                let combatant = &self.combatants[*idx];
+                combatant.side == side && combatant.is_fighting()
+            })
+            .collect()
+    }
+
+    /// Index of the first combatant of the given side on the field.
+    pub fn active(&self, side: Side) -> Option<usize> {
+        self.actives(side).first().copied()
+    }
+
+    /// Indexes of the combatants of the given side waiting for their turn, able to fight.
+    pub fn bench(&self, side: Side) -> Vec<usize> {
+        (0..self.combatants.len())
+            .filter(|idx| {
+                let combatant = &self.combatants[*idx];
+                combatant.side == side && !combatant.active && !combatant.is_ko()
+            })
+            .collect()
+    }
+
+    /// Send combatants waiting for their turn, in order, to take the free slots of a side.
+    pub fn fill_slots(&mut self, side: Side) -> Vec<BattleEvent> {
+        let free = self.slots.saturating_sub(self.actives(side).len());
+        self.bench(side)
+            .into_iter()
+            .take(free)
+            .flat_map(|target| self.send_out(target))
+            .collect()
+    }
+
+    /// Whether the player has to choose who replaces a creature knocked out.
+    pub fn needs_replacement(&self) -> bool {
+        self.actives(Side::Player).len() < self.slots && !self.bench(Side::Player).is_empty()
+    }
+
+    /// Put a combatant waiting for its turn on the field.
+    pub fn send_out(&mut self, target: usize) -> Vec<BattleEvent> {
+        let combatant = &mut self.combatants[target];
+        if combatant.active || combatant.is_ko() {
+            return vec![];
+        }
+        combatant.enter();
+        vec![BattleEvent::SentOut { target }]
+    }
+
+    /// Return the side that won, if any.
+    pub fn winner(&self) -> Option<Side> {
+        let standing = |side: Side| self.combatants.iter().any(|c| c.side == side && !c.is_ko());
+        match (standing(Side::Player), standing(Side::Foe)) {
+            (true, false) => Some(Side::Player),
+            (false, _) => Some(Side::Foe),
+            _ => None,
+        }
+    }
+
+    /// Combatants an attack may be aimed at, when the target has to be chosen.
+    /// Empty if the targets do not depend on a choice.
+    pub fn candidates(&self, source: usize, target_type: Target) -> Vec<usize> {
+        let side = self.combatants[source].side;
+        match target_type {
+            Target::Enemy => self.actives(side.opponent()),
+            Target::Ally => self.actives(side),
+            _ => vec![],
+        }
+    }
+
+    /// Combatants actually hit by an attack of `source` aimed at `chosen`.
+    pub fn targets(&self, source: usize, target_type: Target, chosen: usize) -> Vec<usize> {
+        let side = self.combatants[source].side;
+        let mut all = self.actives(Side::Player);
+        all.extend(self.actives(Side::Foe));
+        match target_type {
+            Target::Enemy | Target::Ally => vec![chosen],
+            Target::OneSelf => vec![source],
+            Target::Enemies => self.actives(side.opponent()),
+            Target::Allies => self.actives(side),
+            Target::All => all,
+            Target::AllButSelf => all.into_iter().filter(|idx| *idx != source).collect(),
+        }
+    }
+
+    /// Queue the action of a combatant for this round, replacing any previous one.
+    pub fn choose(&mut self, action: BattleAction) {
+        self.actions.retain(|a| a.source() != action.source());
+        self.actions.push(action);
+    }
+
+    /// Next combatant of the side on the field without an action for this round.
+    pub fn next_to_choose(&self, side: Side) -> Option<usize> {
+        self.actives(side)
+            .into_iter()
+            .find(|idx| self.actions.iter().all(|a| a.source() != *idx))
+    }
+
+    /// Order the actions of the round: switches first, then items, then fastest first,
+    /// then the player's side, then the order of the combatants.
+    fn schedule(&mut self, dex: &Dex) -> Vec<BattleAction> {
+        let mut actions = std::mem::take(&mut self.actions);
+        actions.sort_by_key(|action| {
+            let source = action.source();
+            let combatant = &self.combatants[source];
+            (
+                action.priority(),
+                std::cmp::Reverse(combatant.stats(dex).speed),
+                combatant.side != Side::Player,
+                source,
+            )
+        });
+        actions
+    }
+
+    /// Play all the actions of the round in order, then end the turn.
+    /// Combatants fainting mid-round do not act, and attacks aimed at them go to
+    /// the next combatant of the same side. Foes knocked out are replaced right away,
+    /// the player chooses their replacements.
+    pub fn play_round(&mut self, dex: &Dex) -> Vec<BattleEvent> {
+        let mut events = Vec::new();
+        for action in self.schedule(dex) {
+            if self.winner().is_some() {
+                break;
+            }
+            if let Some(action) = self.retarget(action) {
+                events.extend(self.resolve(action, dex));
+            }
+        }
+        if self.winner().is_none() {
+            events.extend(self.end_turn(dex));
+        }
+        events.extend(self.fill_slots(Side::Foe));
+        if let Some(winner) = self.winner() {
+            events.push(BattleEvent::Ended { winner });
+            if winner == Side::Player {
+                events.extend(self.reward(dex));
+            }
+        }
+        events
+    }
+
+    /// Share the XP of the defeated foes: every creature of the player which entered
+    /// the field and is still standing earns all of it.
+    fn reward(&mut self, dex: &Dex) -> Vec<BattleEvent> {
+        let amount: u32 = self
+            .combatants
+            .iter()
+            .filter(|c| c.side == Side::Foe)
+            .map(|c| c.member.xp_reward())
+            .sum();
+        let mut events = Vec::new();
+        for (target, combatant) in self.combatants.iter_mut().enumerate() {
+            if combatant.side != Side::Player || !combatant.entered || combatant.is_ko() {
+                continue;
+            }
+            events.push(BattleEvent::XpGained { target, amount });
+            for level in combatant.member.gain_xp(amount, dex) {
+                events.push(BattleEvent::LeveledUp { target, level });
+            }
+        }
+        events
+    }
+
+    /// Point the action at a combatant still fighting, if needed and possible.
+    /// Attacks with a chosen target go to the next combatant of its side, the others
+    /// are aimed at their source and reach whoever is on the field when they happen.
+    fn retarget(&self, action: BattleAction) -> Option<BattleAction> {
+        match action {
+            BattleAction::Attack {
+                source,
+                attack,
+                target,
+            } => {
+                let target = match attack.target_type() {
+                    Target::Enemy | Target::Ally if self.combatants[target].is_fighting() => target,
+                    Target::Enemy | Target::Ally => self.active(self.combatants[target].side)?,
+                    Target::OneSelf
+                    | Target::Enemies
+                    | Target::Allies
+                    | Target::All
+                    | Target::AllButSelf => source,
+                };
+                Some(BattleAction::Attack {
+                    source,
+                    attack,
+                    target,
+                })
+            }
+            // the replacement may have been knocked out or sent out already
+            BattleAction::Switch { target, .. } => {
+                let combatant = &self.combatants[target];
+                (!combatant.active && !combatant.is_ko()).then_some(action)
+            }
+            // an item is kept when its target left the field
+            BattleAction::UseItem { target, .. } => {
+                self.combatants[target].is_fighting().then_some(action)
+            }
+        }
+    }
+
+    /// Apply an action, and return what happened.
+    fn resolve(&mut self, action: BattleAction, dex: &Dex) -> Vec<BattleEvent> {
+        let mut events = Vec::new();
+        match action {
+            BattleAction::Attack {
+                source,
+                attack,
+                target,
+            } => {
+                if !self.combatants[source].is_fighting() {
+                    return events;
+                }
+                if let Some(status @ Status::Freeze) = self.combatants[source].effects.status() {
+                    events.push(BattleEvent::Immobilized { source, status });
+                    return events;
+                }
+                events.push(BattleEvent::AttackUsed {
+                    source,
+                    attack: attack.name(),
+                });
+                for target in self.targets(source, attack.target_type(), target) {
+                    // area attacks may knock out someone before reaching the next one
+                    if !self.combatants[target].is_ko() {
+                        let mut rng = rand::rng();
+                        self.hit(source, target, attack.as_ref(), dex, &mut rng, &mut events);
+                    }
+                }
+            }
+            BattleAction::Switch { source, target } => {
+                if !self.combatants[source].is_fighting() {
+                    return events;
+                }
+                self.combatants[source].leave();
+                events.push(BattleEvent::Withdrawn { source });
+                events.extend(self.send_out(target));
+            }
+            BattleAction::UseItem {
+                source,
+                item,
+                target,
+            } => {
+                if !self.combatants[source].is_fighting() {
+                    return events;
+                }
+                let mut outcome = Vec::new();
+                let amount = item.heal(&mut self.combatants[target].member, dex);
+                if amount > 0 {
+                    outcome.push(BattleEvent::Healed { target, amount });
+                }
+                for effect in item.effects.iter() {
+                    outcome.extend(self.apply_effect(target, effect, dex));
+                }
+                // an item doing nothing is kept
+                let item = item.name.clone();
+                if outcome.is_empty() {
+                    events.push(BattleEvent::ItemWasted { target, item });
+                } else {
+                    events.push(BattleEvent::ItemUsed { target, item });
+                    events.extend(outcome);
+                }
+            }
+        }
+        events
+    }
+
+    /// Land an attack on `target`: roll for a miss and a critical hit, then deal the damage
+    /// and apply the effects. Attacks on oneself and on allies do not miss.
+    fn hit(
+        &mut self,
+        source: usize,
+        target: usize,
+        attack: &dyn Attack,
+        dex: &Dex,
+        rng: &mut impl Rng,
+        events: &mut Vec<BattleEvent>,
+    ) {
+        if self.combatants[target].side != self.combatants[source].side {
+            let accuracy = self.combatants[source].stats(dex).accuracy as u32;
+            let dodge = self.combatants[target].stats(dex).dodge.min(100) as u32;
+            // in %
+            let hit_chance = accuracy * (100 - dodge) / 100;
+            if rng.random_range(0..100) >= hit_chance {
+                events.push(BattleEvent::Missed { source, target });
+                return;
+            }
+        }
+        if let Some(power) = attack.damage() {
+            let mut multiplier = dex.multiplier(attack, &self.combatants[target].member);
+            let effectiveness = Effectiveness::from(multiplier);
+            if effectiveness != Effectiveness::Normal {
+                events.push(BattleEvent::Effective {
+                    target,
+                    effectiveness,
+                });
+            }
+            multiplier *= dex.weight_multiplier(
+                attack,
+                &self.combatants[source].member,
+                &self.combatants[target].member,
+            );
+            if multiplier > 0. && rng.random_range(0..100) < attack.crit_chance() {
+                events.push(BattleEvent::CriticalHit { target });
+                multiplier *= CRIT_MULTIPLIER;
+            }
+            let amount = self.damage(source, target, power, multiplier, dex);
+            events.push(BattleEvent::Damaged { target, amount });
+            events.extend(self.combatants[target].hurt(target, amount));
+        }
+        if !self.combatants[target].is_ko() {
+            for effect in attack.effects() {
+                events.extend(self.apply_effect(target, &effect, dex));
+            }
+        }
+    }
+
+    /// Apply an effect to `target`, and return what happened, if anything.
+    fn apply_effect(&mut self, target: usize, effect: &Effect, dex: &Dex) -> Option<BattleEvent> {
+        let Combatant {
+            member, effects, ..
+        } = &mut self.combatants[target];
+        match *effect {
+            Effect::StatsChange {
+                stat,
+                stages,
+                turns,
+            } => {
+                let stages = effects.change_stat(stat, stages, turns);
+                (stages != 0).then_some(BattleEvent::StatChanged {
+                    target,
+                    stat,
+                    stages,
+                })
+            }
+            Effect::StatusChange {
+                status,
+                turns,
+                chance,
+            } => {
+                let roll = rand::rng().random_range(0..100);
+                (roll < chance && effects.inflict(status, turns))
+                    .then_some(BattleEvent::StatusInflicted { target, status })
+            }
+            Effect::Cleanse => effects
+                .cleanse()
+                .then_some(BattleEvent::Cleansed { target }),
+            Effect::Heal { percent } => {
+                let max_hp = member.max_hp(dex);
+                let amount = (max_hp as u32 * percent as u32 / 100).max(1) as u8;
+                let amount = amount.min(max_hp.saturating_sub(member.hp));
+                member.hp += amount;
+                (amount > 0).then_some(BattleEvent::Healed { target, amount })
+            }
+        }
+    }
+
+    /// End of a turn: statuses hurt, then effects wear off.
+    fn end_turn(&mut self, dex: &Dex) -> Vec<BattleEvent> {
+        let mut events = Vec::new();
+        for (target, combatant) in self.combatants.iter_mut().enumerate() {
+            if !combatant.is_fighting() {
+                continue;
+            }
+            let hurting = match combatant.effects.status() {
+                Some(status @ Status::Burn) => Some((status, 16)),
+                Some(status @ Status::Poison) => Some((status, 8)),
+                _ => None,
+            };
+            if let Some((status, divider)) = hurting {
+                let amount = (combatant.member.max_hp(dex) / divider).max(1);
+                events.push(BattleEvent::StatusHurt {
+                    target,
+                    status,
+                    amount,
+                });
+                if let Some(event) = combatant.hurt(target, amount) {
+                    events.push(event);
+                    continue;
+                }
+            }
+            let (expired, cured) = combatant.effects.tick();
+            events.extend(
+                expired
+                    .into_iter()
+                    .map(|stat| BattleEvent::StatRestored { target, stat }),
+            );
+            if let Some(status) = cured {
+                events.push(BattleEvent::StatusCured { target, status });
+            }
+        }
+        events
+    }
+
+    /// Damage dealt by `source` to `target` with an attack of the given power and multiplier.
+    /// Only useless attacks deal no damage.
+    pub fn damage(
+        &self,
+        source: usize,
+        target: usize,
+        power: u8,
+        multiplier: f32,
+        dex: &Dex,
+    ) -> u8 {
+        if multiplier <= 0. {
+            return 0;
+        }
+        let attack = self.combatants[source].stats(dex).attack as f32;
+        let defense = self.combatants[target].stats(dex).defense.max(1) as f32;
+        (power as f32 * multiplier * attack / defense)
+            .round()
+            .clamp(1., u8::MAX as f32) as u8
+    }
+
+    /// Chance for the player to flee, in percent. Always possible when faster than every foe,
+    /// otherwise it depends on the speed ratio and gets easier with each attempt.
+    pub fn flee_chance(&self, dex: &Dex) -> u32 {
+        let fastest = |side| {
+            self.actives(side)
+                .into_iter()
+                .map(|idx| self.combatants[idx].stats(dex).speed as u32)
+                .max()
+                .unwrap_or(0)
+        };
+        let (player, foe) = (fastest(Side::Player), fastest(Side::Foe));
+        if player >= foe {
+            return 100;
+        }
+        (50 * player / foe + FLEE_BONUS_PER_ATTEMPT * self.flee_attempts).min(100)
+    }
+
+    /// Roll the chance to flee. A failure counts as an attempt.
+    pub fn flee(&mut self, dex: &Dex) -> BattleEvent {
+        if rand::rng().random_range(0..100) < self.flee_chance(dex) {
+            BattleEvent::Fled
+        } else {
+            self.flee_attempts += 1;
+            BattleEvent::FleeFailed
+        }
+    }
+
+    /// Copy the HP and growth of the player's combatants back into the team.
+    pub fn write_back(&self, team: &mut Team) {
+        for combatant in &self.combatants {
+            if let Some(slot) = combatant.team_slot
+                && let Some(member) = team.0.get_mut(slot)
+            {
+                member.hp = combatant.member.hp;
+                member.level = combatant.member.level;
+                member.xp = combatant.member.xp;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        dex::{AttackId, CreatureId},
+        fight::effects::MAX_STAGES,
+    };
+
+    fn member(id: &str, dex: &Dex) -> TeamMember {
+        let creature = &dex.get_creature(&CreatureId(id.to_string())).unwrap().0;
+        TeamMember::new(creature, dex)
+    }
+
+    fn attack(id: &str, dex: &Dex) -> Arc<dyn Attack + Send + Sync> {
+        dex.get_attack(&AttackId(id.to_string())).unwrap()
+    }
+
+    /// A fight of the given creatures against the given foes, all on the field.
+    fn battle(team: &[&str], foes: &[&str], dex: &Dex) -> Battle {
+        let team = Team(team.iter().map(|id| member(id, dex)).collect());
+        let selection: Vec<usize> = (0..team.0.len()).collect();
+        let foes = foes.iter().map(|id| member(id, dex)).collect();
+        Battle::new(&team, &selection, foes, 3, AiKind::Random)
+    }
+
+    /// Dice always rolling the same value: 0 makes every roll the lowest, `u64::MAX` the highest.
+    struct Rolls(u64);
+
+    impl Rolls {
+        /// Rolls of `percent` out of 100.
+        fn at(percent: u64) -> Self {
+            Rolls(percent * (u64::MAX / 100) + u64::MAX / 200)
+        }
+    }
+
+    impl rand::RngCore for Rolls {
+        fn next_u32(&mut self) -> u32 {
+            (self.0 >> 32) as u32
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            self.0
+        }
+
+        fn fill_bytes(&mut self, dst: &mut [u8]) {
+            dst.fill(self.0 as u8);
+        }
+    }
+
+    /// Make sure every attack of `source` lands on `target`.
+    fn never_miss(battle: &mut Battle, target: usize) {
+        battle.combatants[target]
+            .effects
+            .change_stat(Stat::Dodge, -MAX_STAGES, 10);
+    }
+
+    #[test]
+    fn slots_are_filled_in_order() {
+        let dex = Dex::from_base_files();
+        let team = Team(vec![
+            member("flammiaou", &dex),
+            member("ratcendre", &dex),
+            member("hirondflamme", &dex),
+        ]);
+        let battle = Battle::new(
+            &team,
+            &[2, 0, 1],
+            vec![member("matoulot", &dex)],
+            1,
+            AiKind::Random,
+        );
+        assert_eq!(battle.actives(Side::Player), vec![0]);
+        assert_eq!(battle.combatants[0].team_slot, Some(2));
+        assert_eq!(battle.bench(Side::Player), vec![1, 2]);
+        assert_eq!(battle.actives(Side::Foe), vec![3]);
+    }
+
+    #[test]
+    fn targets_follow_the_target_of_the_attack() {
+        let dex = Dex::from_base_files();
+        let battle = battle(
+            &["flammiaou", "ratcendre"],
+            &["matoulot", "hydrouris"],
+            &dex,
+        );
+        assert_eq!(battle.candidates(0, Target::Enemy), vec![2, 3]);
+        assert_eq!(battle.candidates(0, Target::Ally), vec![0, 1]);
+        assert!(battle.candidates(0, Target::All).is_empty());
+        assert_eq!(battle.targets(0, Target::Enemy, 3), vec![3]);
+        assert_eq!(battle.targets(0, Target::OneSelf, 3), vec![0]);
+        assert_eq!(battle.targets(0, Target::Enemies, 0), vec![2, 3]);
+        assert_eq!(battle.targets(0, Target::Allies, 0), vec![0, 1]);
+        assert_eq!(battle.targets(0, Target::All, 0), vec![0, 1, 2, 3]);
+        assert_eq!(battle.targets(0, Target::AllButSelf, 0), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn area_attacks_hit_everyone_on_the_field() {
+        let dex = Dex::from_base_files();
+        let mut battle = battle(
+            &["flammiaou", "ratcendre"],
+            &["matoulot", "hydrouris"],
+            &dex,
+        );
+        for target in 1..4 {
+            never_miss(&mut battle, target);
+        }
+        // the aim of an area attack does not matter
+        battle.choose(BattleAction::Attack {
+            source: 0,
+            attack: attack("scratch", &dex),
+            target: 3,
+        });
+        let events = battle.play_round(&dex);
+        let mut damaged: Vec<usize> = events
+            .iter()
+            .filter_map(|e| match e {
+                BattleEvent::Damaged { target, .. } => Some(*target),
+                _ => None,
+            })
+            .collect();
+        damaged.sort();
+        assert_eq!(damaged, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn switches_then_items_then_fastest_first() {
+        let dex = Dex::from_base_files();
+        let mut battle = battle(
+            &["flammiaou", "ratcendre"],
+            &["matoulot", "hydrouris"],
+            &dex,
+        );
+        // a spare creature of the player, to switch with
+        battle
+            .combatants
+            .push(Combatant::new(Side::Player, member("sourilex", &dex), None));
+        let kick = attack("kick", &dex);
+        for source in [0, 2, 3] {
+            battle.choose(BattleAction::Attack {
+                source,
+                attack: Arc::clone(&kick),
+                target: 0,
+            });
+        }
+        battle.choose(BattleAction::Switch {
+            source: 1,
+            target: 4,
+        });
+        let speed = |idx: usize| battle.combatants[idx].stats(&dex).speed;
+        let mut attackers = vec![0, 2, 3];
+        attackers.sort_by_key(|idx| (std::cmp::Reverse(speed(*idx)), *idx));
+        let order: Vec<usize> = battle
+            .schedule(&dex)
+            .iter()
+            .map(BattleAction::source)
+            .collect();
+        assert_eq!(order[0], 1);
+        assert_eq!(order[1..], attackers);
+        assert!(battle.actions.is_empty());
+    }
+
+    #[test]
+    fn damage_grows_with_attack_and_shrinks_with_defense() {
+        let dex = Dex::from_base_files();
+        let battle = battle(&["flammiaou"], &["ratcendre"], &dex);
+        let attack = battle.combatants[0].stats(&dex).attack as f32;
+        let defense = battle.combatants[1].stats(&dex).defense as f32;
+        let expected = (20. * 2. * attack / defense).round() as u8;
+        assert_eq!(battle.damage(0, 1, 20, 2., &dex), expected);
+        // useless attacks deal nothing, the others at least 1
+        assert_eq!(battle.damage(0, 1, 20, 0., &dex), 0);
+        assert_eq!(battle.damage(0, 1, 1, 0.01, &dex), 1);
+    }
+
+    #[test]
+    fn attacks_on_the_other_side_miss_on_high_rolls() {
+        let dex = Dex::from_base_files();
+        let mut battle = battle(&["flammiaou"], &["ratcendre"], &dex);
+        let mut events = Vec::new();
+        let kick = attack("kick", &dex);
+        battle.hit(0, 1, kick.as_ref(), &dex, &mut Rolls(u64::MAX), &mut events);
+        assert!(matches!(
+            events[..],
+            [BattleEvent::Missed {
+                source: 0,
+                target: 1
+            }]
+        ));
+    }
+
+    #[test]
+    fn attacks_on_allies_never_miss() {
+        let dex = Dex::from_base_files();
+        let mut battle = battle(&["flammiaou", "ratcendre"], &["mulovol"], &dex);
+        battle.combatants[1].effects.inflict(Status::Poison, 3);
+        let mut events = Vec::new();
+        let clean = attack("clean", &dex);
+        battle.hit(
+            0,
+            1,
+            clean.as_ref(),
+            &dex,
+            &mut Rolls(u64::MAX),
+            &mut events,
+        );
+        assert!(matches!(events[..], [BattleEvent::Cleansed { target: 1 }]));
+    }
+
+    #[test]
+    fn low_rolls_land_critical_hits() {
+        let dex = Dex::from_base_files();
+        let mut battle = battle(&["flammiaou"], &["ratcendre"], &dex);
+        let kick = attack("kick", &dex);
+        let multiplier = dex.multiplier(kick.as_ref(), &battle.combatants[1].member)
+            * dex.weight_multiplier(
+                kick.as_ref(),
+                &battle.combatants[0].member,
+                &battle.combatants[1].member,
+            );
+        let expected = battle.damage(0, 1, kick.damage().unwrap(), multiplier * 1.5, &dex);
+        let mut events = Vec::new();
+        battle.hit(0, 1, kick.as_ref(), &dex, &mut Rolls(0), &mut events);
+        assert!(
+            events
+                .iter()
+                .any(|event| matches!(event, BattleEvent::CriticalHit { target: 1 }))
+        );
+        assert!(events.iter().any(|event| matches!(
+            event,
+            BattleEvent::Damaged { target: 1, amount } if *amount == expected
+        )));
+    }
+
+    #[test]
+    fn heavier_attackers_hit_harder_with_weight_based_attacks() {
+        let dex = Dex::from_base_files();
+        // a 5 kg cat against a 0.5 kg mouse
+        let mut battle = battle(&["flammiaou"], &["ratcendre"], &dex);
+        let charge = attack("charge", &dex);
+        let (cat, mouse) = (&battle.combatants[0].member, &battle.combatants[1].member);
+        let heavier = dex.weight_multiplier(charge.as_ref(), cat, mouse);
+        let lighter = dex.weight_multiplier(charge.as_ref(), mouse, cat);
+        assert!(heavier > 1. && lighter < 1.);
+        let neutral = battle.damage(0, 1, 25, dex.multiplier(charge.as_ref(), mouse), &dex);
+        never_miss(&mut battle, 1);
+        // high enough not to be a critical hit, low enough to hit
+        let mut events = Vec::new();
+        battle.hit(0, 1, charge.as_ref(), &dex, &mut Rolls::at(50), &mut events);
+        let dealt = events.iter().find_map(|event| match event {
+            BattleEvent::Damaged { target: 1, amount } => Some(*amount),
+            _ => None,
+        });
+        assert!(dealt.is_some_and(|dealt| dealt > neutral), "{events:?}");
+    }
+
+    #[test]
+    fn knocked_out_foe_ends_the_fight_and_rewards_the_player() {
+        let dex = Dex::from_base_files();
+        let mut battle = battle(&["flammiaou"], &["ratcendre"], &dex);
+        never_miss(&mut battle, 1);
+        battle.combatants[1].member.hp = 1;
+        battle.choose(BattleAction::Attack {
+            source: 0,
+            attack: attack("lancegaz", &dex),
+            target: 1,
+        });
+        let events = battle.play_round(&dex);
+        assert!(
+            events
+                .iter()
+                .any(|e| matches!(e, BattleEvent::Fainted { target: 1 }))
+        );
+        assert!(matches!(
+            events
+                .iter()
+                .find(|e| matches!(e, BattleEvent::Ended { .. })),
+            Some(BattleEvent::Ended {
+                winner: Side::Player
+            })
+        ));
+        let reward = battle.combatants[1].member.xp_reward();
+        assert!(events.iter().any(
+            |e| matches!(e, BattleEvent::XpGained { target: 0, amount } if *amount == reward)
+        ));
+        assert_eq!(battle.winner(), Some(Side::Player));
+    }
+
+    #[test]
+    fn attacks_go_to_the_next_foe_when_their_target_is_knocked_out() {
+        let dex = Dex::from_base_files();
+        let mut battle = battle(&["flammiaou"], &["ratcendre", "hydrouris"], &dex);
+        never_miss(&mut battle, 2);
+        battle.combatants[1].member.hp = 0;
+        let action = BattleAction::Attack {
+            source: 0,
+            attack: attack("lancegaz", &dex),
+            target: 1,
+        };
+        assert!(matches!(
+            battle.retarget(action),
+            Some(BattleAction::Attack { target: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn healing_stops_at_the_max_hp() {
+        let dex = Dex::from_base_files();
+        let mut battle = battle(&["flammiaou"], &["ratcendre"], &dex);
+        let max_hp = battle.combatants[0].member.max_hp(&dex);
+        battle.combatants[0].member.hp = max_hp - 1;
+        let heal = Effect::Heal { percent: 25 };
+        assert!(matches!(
+            battle.apply_effect(0, &heal, &dex),
+            Some(BattleEvent::Healed {
+                target: 0,
+                amount: 1
+            })
+        ));
+        assert_eq!(battle.combatants[0].member.hp, max_hp);
+        assert!(battle.apply_effect(0, &heal, &dex).is_none());
+    }
+
+    #[test]
+    fn items_doing_nothing_are_not_used() {
+        let dex = Dex::from_base_files();
+        let mut battle = battle(&["flammiaou"], &["ratcendre"], &dex);
+        let potion = Item {
+            name: String::from("Potion"),
+            description: String::new(),
+            heal: Some(20),
+            effects: vec![],
+            evolution: false,
+        };
+        let use_potion = BattleAction::UseItem {
+            source: 0,
+            item: potion,
+            target: 0,
+        };
+        let events = battle.resolve(use_potion.clone(), &dex);
+        assert!(matches!(
+            events[..],
+            [BattleEvent::ItemWasted { target: 0, .. }]
+        ));
+        battle.combatants[0].member.hp -= 5;
+        let events = battle.resolve(use_potion, &dex);
+        assert!(matches!(
+            events[..],
+            [
+                BattleEvent::ItemUsed { target: 0, .. },
+                BattleEvent::Healed {
+                    target: 0,
+                    amount: 5
+                }
+            ]
+        ));
+    }
+
+    #[test]
+    fn flee_is_certain_when_faster_and_easier_with_each_attempt() {
+        let dex = Dex::from_base_files();
+        let mut battle = battle(&["flammiaou"], &["ratcendre"], &dex);
+        let speed = |battle: &Battle, idx: usize| battle.combatants[idx].stats(&dex).speed as u32;
+        if speed(&battle, 0) >= speed(&battle, 1) {
+            assert_eq!(battle.flee_chance(&dex), 100);
+        }
+        // slow the player down until the foe is faster
+        battle.combatants[0]
+            .effects
+            .change_stat(Stat::Speed, -MAX_STAGES, 10);
+        battle.combatants[1]
+            .effects
+            .change_stat(Stat::Speed, MAX_STAGES, 10);
+        let (player, foe) = (speed(&battle, 0), speed(&battle, 1));
+        assert!(player < foe);
+        assert_eq!(battle.flee_chance(&dex), 50 * player / foe);
+        battle.flee_attempts = 2;
+        assert_eq!(
+            battle.flee_chance(&dex),
+            (50 * player / foe + 2 * FLEE_BONUS_PER_ATTEMPT).min(100)
+        );
+    }
+
+    #[test]
+    fn growth_is_written_back_to_the_team() {
+        let dex = Dex::from_base_files();
+        let mut team = Team(vec![member("flammiaou", &dex)]);
+        let mut battle = Battle::new(
+            &team,
+            &[0],
+            vec![member("ratcendre", &dex)],
+            1,
+            AiKind::Random,
+        );
+        battle.combatants[0].member.hp = 3;
+        battle.combatants[0].member.gain_xp(1000, &dex);
+        battle.write_back(&mut team);
+        assert_eq!(team.0[0].hp, battle.combatants[0].member.hp);
+        assert_eq!(team.0[0].level, battle.combatants[0].member.level);
+        assert!(team.0[0].level > 1);
+    }
+}
@@ -0,0 +1,185 @@
+use bevy::platform::collections::HashMap;
+
+use crate::dex::{Stat, Stats, Status};
+
+/// Stat stages are capped in both directions.
+pub const MAX_STAGES: i8 = 3;
+/// Dodge and accuracy are percentages, each stage adds or removes that many points.
+const PERCENT_PER_STAGE: i8 = 10;
+
+/// Effects currently applied to a combatant. They only last for the fight.
+///
+/// Stat stages stack: applying the same stat again adds the stages (capped to `MAX_STAGES`)
+/// and keeps the longest duration. A combatant only suffers from one status at a time,
+/// a new status does not replace the current one.
+#[derive(Clone, Default)]
+pub struct ActiveEffects {
+    /// Stages and turns left for each altered stat.
+    stages: HashMap<Stat, (i8, u8)>,
+    /// Current status and turns left.
+    status: Option<(Status, u8)>,
+}
+
+impl ActiveEffects {
+    /// Add stages to a stat, and return how many were actually added.
+    pub fn change_stat(&mut self, stat: Stat, stages: i8, turns: u8) -> i8 {
+        let (current, turns_left) = self.stages.get(&stat).copied().unwrap_or((0, 0));
+        let new = (current + stages).clamp(-MAX_STAGES, MAX_STAGES);
+        if new == 0 {
+            self.stages.remove(&stat);
+        } else {
+            self.stages.insert(stat, (new, turns_left.max(turns)));
+        }
+        new - current
+    }
+
+    /// Inflict a status, return `false` if there already is one.
+    pub fn inflict(&mut self, status: Status, turns: u8) -> bool {
+        if self.status.is_some() {
+            return false;
+        }
+        self.status = Some((status, turns));
+        true
+    }
+
+    pub fn status(&self) -> Option<Status> {
+        self.status.map(|(status, _)| status)
+    }
+
+    /// Remove lowered stats and statuses, return `true` if anything was removed.
+    pub fn cleanse(&mut self) -> bool {
+        let before = self.stages.len();
+        self.stages.retain(|_, (stages, _)| *stages > 0);
+        let cleansed = self.stages.len() != before || self.status.is_some();
+        self.status = None;
+        cleansed
+    }
+
+    /// Stats altered by the current stages.
+    pub fn apply_to(&self, mut stats: Stats) -> Stats {
+        for (stat, (stages, _)) in self.stages.iter() {
+            match stat {
+                Stat::Attack => stats.attack = scale(stats.attack, *stages),
+                Stat::Defense => stats.defense = scale(stats.defense, *stages),
+                Stat::Speed => stats.speed = scale(stats.speed, *stages),
+                Stat::Dodge => stats.dodge = shift(stats.dodge, *stages),
+                Stat::Accuracy => stats.accuracy = shift(stats.accuracy, *stages),
+            }
+        }
+        stats
+    }
+
+    /// Spend a turn. Return the stats back to normal and the cured status, if any.
+    pub fn tick(&mut self) -> (Vec<Stat>, Option<Status>) {
+        let mut expired = Vec::new();
+        self.stages.retain(|stat, (_, turns)| {
+            *turns = turns.saturating_sub(1);
+            if *turns == 0 {
+                expired.push(*stat);
+            }
+            *turns > 0
+        });
+        let mut cured = None;
+        if let Some((status, turns)) = self.status.as_mut() {
+            *turns = turns.saturating_sub(1);
+            if *turns == 0 {
+                cured = Some(*status);
+                self.status = None;
+            }
+        }
+        (expired, cured)
+    }
+}
+
+/// Multiply a stat by (2 + stages) / 2 when raised, and 2 / (2 - stages) when lowered.
+fn scale(value: u8, stages: i8) -> u8 {
+    let ratio = if stages >= 0 {
+        (2. + stages as f32) / 2.
+    } else {
+        2. / (2. - stages as f32)
+    };
+    (value as f32 * ratio).round().clamp(1., u8::MAX as f32) as u8
+}
+
+/// Add or remove percentage points.
+fn shift(value: u8, stages: i8) -> u8 {
+    (value as i16 + (stages * PERCENT_PER_STAGE) as i16).clamp(0, 100) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats() -> Stats {
+        Stats {
+            hp: 50,
+            attack: 40,
+            defense: 40,
+            speed: 40,
+            dodge: 10,
+            accuracy: 90,
+        }
+    }
+
+    #[test]
+    fn stages_stack_up_to_the_cap() {
+        let mut effects = ActiveEffects::default();
+        assert_eq!(effects.change_stat(Stat::Attack, 2, 3), 2);
+        assert_eq!(effects.change_stat(Stat::Attack, 2, 3), 1);
+        assert_eq!(effects.change_stat(Stat::Attack, 1, 3), 0);
+        assert_eq!(effects.apply_to(stats()).attack, 100);
+        assert_eq!(effects.change_stat(Stat::Defense, -5, 3), -MAX_STAGES);
+        assert_eq!(effects.apply_to(stats()).defense, 16);
+        // back to normal, the stat is not altered anymore
+        assert_eq!(effects.change_stat(Stat::Defense, 3, 3), 3);
+        assert_eq!(effects.apply_to(stats()).defense, 40);
+    }
+
+    #[test]
+    fn percentages_move_by_points() {
+        let mut effects = ActiveEffects::default();
+        effects.change_stat(Stat::Dodge, 2, 3);
+        effects.change_stat(Stat::Accuracy, 3, 3);
+        let stats = effects.apply_to(stats());
+        assert_eq!(stats.dodge, 30);
+        assert_eq!(stats.accuracy, 100);
+    }
+
+    #[test]
+    fn stacked_stages_keep_the_longest_duration() {
+        let mut effects = ActiveEffects::default();
+        effects.change_stat(Stat::Speed, 1, 3);
+        effects.change_stat(Stat::Speed, 1, 1);
+        assert_eq!(effects.tick(), (vec![], None));
+        assert_eq!(effects.tick(), (vec![], None));
+        assert_eq!(effects.tick(), (vec![Stat::Speed], None));
+        assert_eq!(effects.apply_to(stats()).speed, 40);
+    }
+
+    #[test]
+    fn a_status_is_neither_refreshed_nor_replaced() {
+        let mut effects = ActiveEffects::default();
+        assert!(effects.inflict(Status::Poison, 2));
+        assert!(!effects.inflict(Status::Burn, 5));
+        assert!(!effects.inflict(Status::Poison, 5));
+        assert_eq!(effects.status(), Some(Status::Poison));
+        assert_eq!(effects.tick(), (vec![], None));
+        assert_eq!(effects.tick(), (vec![], Some(Status::Poison)));
+        assert_eq!(effects.status(), None);
+        assert!(effects.inflict(Status::Burn, 5));
+    }
+
+    #[test]
+    fn cleanse_keeps_raised_stats_only() {
+        let mut effects = ActiveEffects::default();
+        assert!(!effects.cleanse());
+        effects.change_stat(Stat::Attack, 1, 3);
+        assert!(!effects.cleanse());
+        effects.change_stat(Stat::Speed, -1, 3);
+        effects.inflict(Status::Freeze, 3);
+        assert!(effects.cleanse());
+        assert_eq!(effects.status(), None);
+        let stats = effects.apply_to(stats());
+        assert_eq!((stats.attack, stats.speed), (60, 40));
+    }
+}
@@ -0,0 +1,109 @@
+use bevy::prelude::*;
+
+mod ai;
+mod battle;
+mod effects;
+mod systems;
+
+pub use ai::AiKind;
+pub use battle::{Battle, BattleAction, BattleEvent, Side};
+use systems::*;
+
+use crate::{
+    AppState,
+    team::{Team, TeamMember},
+};
+
+/// Describe the state of a fight.
+#[derive(States, Debug, Hash, Eq, PartialEq, Clone, Default)]
+#[allow(dead_code)]
+pub enum FightState {
+    /// Player chooses which creatures will fight
+    Start,
+    #[default]
+    /// Player chooses if they want to attack, use item, switch, or flee.
+    MainAction,
+    /// Player chooses which of their creature will do or receive the following things.
+    SourceChoice,
+    /// Player chooses which attack they will use
+    AttackChoice,
+    /// Player chooses the item to use on their guys
+    ItemChoice,
+    /// Player chooses the target of the attack or item
+    TargetChoice,
+    /// Player chooses which creature they will send to replace the current one
+    SwitchChoice,
+    /// The chosen actions happen, fastest creatures first
+    Action,
+    /// Enemies choose their actions
+    EnemyTurn,
+    /// Player won, their creatures got their XP
+    Win,
+    /// Player lost, they will wake up at their last rest point
+    Lose,
+    /// The wild foe joined the team
+    Tamed,
+}
+
+/// Opponent of a trainer battle. Wild battles use the `Creature` resource instead.
+#[derive(Resource, Clone)]
+pub struct Trainer {
+    pub name: String,
+    pub team: Vec<TeamMember>,
+    pub ai: AiKind,
+}
+
+/// Members of the team chosen to fight, by index in the `Team`, in order of entry.
+#[derive(Resource, Default)]
+pub struct TeamSelection(pub Vec<usize>);
+
+impl TeamSelection {
+    /// At the beginning of any fight, the player chooses up to three creatures.
+    pub const MAX: usize = 3;
+
+    /// Add or remove a member from the selection. Fainted members cannot fight.
+    pub fn toggle(&mut self, slot: usize, team: &Team) {
+        if let Some(pos) = self.0.iter().position(|s| *s == slot) {
+            self.0.remove(pos);
+        } else if self.0.len() < Self::MAX && team.0.get(slot).is_some_and(|m| !m.is_ko()) {
+            self.0.push(slot);
+        }
+    }
+}
+
+#[derive(Event)]
+/// Trigger when the player tries to run away from the fight.
+pub struct FleeAttemptEvent;
+
+/// Human readable history of the fight, built from `BattleEvent`s.
+#[derive(Resource, Default)]
+pub struct BattleLog(pub Vec<String>);
+
+pub struct FightPlugin;
+
+impl Plugin for FightPlugin {
+    fn build(&self, app: &mut bevy::app::App) {
+        app.init_state::<FightState>()
+            .init_resource::<BattleLog>()
+            .add_event::<BattleEvent>()
+            .add_event::<FleeAttemptEvent>()
+            .add_systems(OnEnter(AppState::InFight), start_battle)
+            .add_systems(OnExit(AppState::InFight), end_battle)
+            .add_systems(
+                OnExit(FightState::Start),
+                begin_battle.run_if(resource_exists::<TeamSelection>),
+            )
+            .add_systems(
+                OnEnter(FightState::EnemyTurn),
+                choose_enemy_actions.run_if(resource_exists::<Battle>),
+            )
+            .add_systems(
+                OnEnter(FightState::Action),
+                play_round.run_if(resource_exists::<Battle>),
+            )
+            .add_systems(
+                Update,
+                (attempt_flee, record_battle_log).run_if(in_state(AppState::InFight)),
+            );
+    }
+}
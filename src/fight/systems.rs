@@ -0,0 +1,239 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::{
+    LdtkProjectHandle, LevelSelection,
+    assets::{LdtkProject, LevelMetadataAccessor},
+};
+use rand::Rng;
+
+use crate::{
+    AppState,
+    dex::{Creature, Dex},
+    fight::{
+        Battle, BattleEvent, BattleLog, FightState, FleeAttemptEvent, Side, TeamSelection, Trainer,
+    },
+    team::{Team, TeamMember},
+    utils::{read_ai_from_ldtk_fields, read_levels_from_ldtk_fields},
+};
+
+/// Wild encounters are 1v1.
+const WILD_SLOTS: usize = 1;
+/// Trainer fights are 3v3.
+const TRAINER_SLOTS: usize = 3;
+
+/// Let the player choose their creatures before the fight begins.
+pub fn start_battle(
+    mut commands: Commands,
+    mut log: ResMut<BattleLog>,
+    mut next_fight_state: ResMut<NextState<FightState>>,
+) {
+    commands.insert_resource(TeamSelection::default());
+    log.0.clear();
+    next_fight_state.set(FightState::Start);
+}
+
+/// Build the `Battle` resource from the selected creatures and the foes.
+///
+/// Trainers bring their own AI, wild creatures use the one of the current level, and a level
+/// in the range of the current level.
+#[allow(clippy::too_many_arguments)]
+pub fn begin_battle(
+    mut commands: Commands,
+    selection: Res<TeamSelection>,
+    wild_creature: Option<Res<Creature>>,
+    trainer: Option<Res<Trainer>>,
+    team: Res<Team>,
+    dex: Res<Dex>,
+    level_selection: Res<LevelSelection>,
+    ldtk_project_entities: Query<&LdtkProjectHandle>,
+    ldtk_project_assets: Res<Assets<LdtkProject>>,
+) {
+    let battle = if let Some(trainer) = trainer {
+        Battle::new(
+            &team,
+            &selection.0,
+            trainer.team.clone(),
+            TRAINER_SLOTS,
+            trainer.ai,
+        )
+    } else {
+        let fields = ldtk_project_entities
+            .single()
+            .ok()
+            .and_then(|handle| ldtk_project_assets.get(handle))
+            .and_then(|project| project.find_raw_level_by_level_selection(&level_selection))
+            .map(|level| level.field_instances.as_slice())
+            .unwrap_or_default();
+        let ai = read_ai_from_ldtk_fields("wild_ai", fields);
+        let level = rand::rng().random_range(read_levels_from_ldtk_fields(fields));
+        let foes = wild_creature
+            .map(|creature| vec![TeamMember::at_level(&creature, level, &dex)])
+            .unwrap_or_default();
+        Battle::new(&team, &selection.0, foes, WILD_SLOTS, ai)
+    };
+    commands.insert_resource(battle);
+    commands.remove_resource::<TeamSelection>();
+}
+
+/// Save the outcome of the fight into the team and forget about it.
+pub fn end_battle(mut commands: Commands, battle: Option<Res<Battle>>, mut team: ResMut<Team>) {
+    if let Some(battle) = battle {
+        battle.write_back(&mut team);
+    }
+    commands.remove_resource::<Battle>();
+}
+
+/// Play the round once every combatant has chosen its action.
+/// The player replaces their creatures knocked out before choosing the next actions.
+pub fn play_round(
+    mut battle: ResMut<Battle>,
+    mut team: ResMut<Team>,
+    mut events: EventWriter<BattleEvent>,
+    mut next_fight_state: ResMut<NextState<FightState>>,
+    dex: Res<Dex>,
+) {
+    events.write_batch(battle.play_round(&dex));
+    battle.write_back(&mut team);
+    let otherwise = if battle.needs_replacement() {
+        FightState::SwitchChoice
+    } else {
+        FightState::MainAction
+    };
+    next_fight_state.set(next_state_after(&battle, otherwise));
+}
+
+/// Each foe on the field lets the AI of the battle choose its action.
+pub fn choose_enemy_actions(
+    mut battle: ResMut<Battle>,
+    mut next_fight_state: ResMut<NextState<FightState>>,
+    dex: Res<Dex>,
+) {
+    let ai = battle.ai.ai();
+    for source in battle.actives(Side::Foe) {
+        if let Some(action) = ai.choose(&battle, source, &dex) {
+            battle.choose(action);
+        }
+    }
+    next_fight_state.set(FightState::Action);
+}
+
+/// Try to run away from a wild fight. Trainers do not let the player go,
+/// and a failed attempt costs the player's turn.
+#[allow(clippy::too_many_arguments)]
+pub fn attempt_flee(
+    mut flee_reader: EventReader<FleeAttemptEvent>,
+    battle: Option<ResMut<Battle>>,
+    trainer: Option<Res<Trainer>>,
+    mut events: EventWriter<BattleEvent>,
+    mut log: ResMut<BattleLog>,
+    dex: Res<Dex>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut next_fight_state: ResMut<NextState<FightState>>,
+) {
+    let Some(mut battle) = battle else {
+        return;
+    };
+    for _ in flee_reader.read() {
+        if let Some(trainer) = trainer.as_ref() {
+            log.0
+                .push(format!("{} does not let you run away!", trainer.name));
+            continue;
+        }
+        let event = battle.flee(&dex);
+        match event {
+            BattleEvent::Fled => next_state.set(AppState::InGame),
+            _ => next_fight_state.set(FightState::EnemyTurn),
+        }
+        events.write(event);
+    }
+}
+
+/// `Win` or `Lose` if the fight is over, `otherwise` if not.
+fn next_state_after(battle: &Battle, otherwise: FightState) -> FightState {
+    match battle.winner() {
+        Some(Side::Player) => FightState::Win,
+        Some(Side::Foe) => FightState::Lose,
+        None => otherwise,
+    }
+}
+
+/// Turn `BattleEvent`s into sentences for the UI.
+pub fn record_battle_log(
+    mut events: EventReader<BattleEvent>,
+    mut log: ResMut<BattleLog>,
+    battle: Option<Res<Battle>>,
+    dex: Res<Dex>,
+) {
+    let Some(battle) = battle else {
+        return;
+    };
+    let name = |idx: usize| battle.combatants[idx].member.name(&dex);
+    for event in events.read() {
+        log.0.push(match event {
+            BattleEvent::AttackUsed { source, attack } => {
+                format!("{} uses {attack}!", name(*source))
+            }
+            BattleEvent::SentOut { target } => format!("{} enters the fight!", name(*target)),
+            BattleEvent::Withdrawn { source } => format!("{} comes back.", name(*source)),
+            BattleEvent::ItemUsed { target, item } => {
+                format!("You use a {item} on {}.", name(*target))
+            }
+            BattleEvent::ItemWasted { target, item } => {
+                format!("The {item} has no effect on {}.", name(*target))
+            }
+            BattleEvent::Healed { target, amount } => {
+                format!("{} gets {amount} HP back.", name(*target))
+            }
+            BattleEvent::Missed { source, target } => {
+                format!("{} misses {}!", name(*source), name(*target))
+            }
+            BattleEvent::CriticalHit { target } => format!("Critical hit on {}!", name(*target)),
+            BattleEvent::Effective {
+                target,
+                effectiveness,
+            } => format!("It's {effectiveness} against {}!", name(*target)),
+            BattleEvent::Damaged { target, amount } => {
+                format!("{} loses {amount} HP.", name(*target))
+            }
+            BattleEvent::StatChanged {
+                target,
+                stat,
+                stages,
+            } => format!(
+                "{}'s {stat} {}!",
+                name(*target),
+                if *stages > 0 { "rises" } else { "falls" }
+            ),
+            BattleEvent::StatRestored { target, stat } => {
+                format!("{}'s {stat} is back to normal.", name(*target))
+            }
+            BattleEvent::StatusInflicted { target, status } => {
+                format!("{} is {status}!", name(*target))
+            }
+            BattleEvent::StatusHurt {
+                target,
+                status,
+                amount,
+            } => format!("{} is {status} and loses {amount} HP.", name(*target)),
+            BattleEvent::Immobilized { source, status } => {
+                format!("{} is {status} and cannot move!", name(*source))
+            }
+            BattleEvent::StatusCured { target, status } => {
+                format!("{} is not {status} anymore.", name(*target))
+            }
+            BattleEvent::Cleansed { target } => format!("{} feels better.", name(*target)),
+            BattleEvent::Fainted { target } => format!("{} is KO!", name(*target)),
+            BattleEvent::Ended {
+                winner: Side::Player,
+            } => String::from("You won the fight!"),
+            BattleEvent::Ended { winner: Side::Foe } => String::from("You lost the fight..."),
+            BattleEvent::XpGained { target, amount } => {
+                format!("{} gains {amount} XP.", name(*target))
+            }
+            BattleEvent::LeveledUp { target, level } => {
+                format!("{} grows to level {level}!", name(*target))
+            }
+            BattleEvent::Fled => String::from("You got away safely!"),
+            BattleEvent::FleeFailed => String::from("You could not get away!"),
+        });
+    }
+}
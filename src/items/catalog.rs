@@ -0,0 +1,155 @@
+use bevy::ecs::resource::Resource;
+use serde::Deserialize;
+
+use crate::{
+    dex::{Dex, DexError, Effect, Entries, check_effects, check_unique, parse_file},
+    team::TeamMember,
+};
+
+/// Every item of the game.
+#[derive(Resource)]
+pub struct ItemCatalog(pub Vec<Item>);
+
+/// Schema of `items.json`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ItemsFile {
+    items: Entries<Item>,
+}
+
+/// Something the player carries, to be used on their creatures.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Item {
+    pub name: String,
+    pub description: String,
+    /// HP restored, fights or not.
+    #[serde(default)]
+    pub heal: Option<u8>,
+    /// Applied during fights only, like the effects of attacks.
+    #[serde(default)]
+    pub effects: Vec<Effect>,
+    /// Makes some creatures evolve, out of fights.
+    #[serde(default)]
+    pub evolution: bool,
+}
+
+impl ItemCatalog {
+    /// Read `items.json`. Faulty items are reported and left out.
+    pub fn new() -> Self {
+        let content = include_str!("../../assets/items/items.json");
+        let mut errors = Vec::new();
+        let catalog = Self::parse("items.json", content, &mut errors);
+        for error in errors.iter() {
+            eprintln!("{error}");
+        }
+        catalog
+    }
+
+    /// Read the items of `content`, the errors going to `errors`.
+    pub fn parse(file: &str, content: &str, errors: &mut Vec<DexError>) -> Self {
+        let Some(items_file) = parse_file::<ItemsFile>(file, content, errors) else {
+            return ItemCatalog(vec![]);
+        };
+        let items = items_file.items.collect(&format!("{file}: items"), errors);
+        check_unique(
+            "name",
+            items
+                .iter()
+                .map(|(path, item)| (path.as_str(), item.name.as_str())),
+            errors,
+        );
+        let items = items
+            .into_iter()
+            .filter(|(path, item)| {
+                let before = errors.len();
+                item.check(path, errors);
+                errors.len() == before
+            })
+            .map(|(_, item)| item.clone())
+            .collect();
+        ItemCatalog(items)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Item> {
+        self.0.iter().find(|item| item.name == name)
+    }
+}
+
+impl Item {
+    /// Check what serde cannot: the amount healed and the effects.
+    fn check(&self, path: &str, errors: &mut Vec<DexError>) {
+        if self.heal == Some(0) {
+            errors.push(DexError::new(
+                &format!("{path}.heal"),
+                "should be positive, or left out",
+            ));
+        }
+        check_effects(&format!("{path}.effects"), &self.effects, errors);
+    }
+
+    /// Whether it does anything in fights.
+    pub fn usable_in_fight(&self) -> bool {
+        self.heal.is_some() || !self.effects.is_empty()
+    }
+
+    /// Whether it does anything outside fights, where effects do not last.
+    pub fn usable_outside_fight(&self) -> bool {
+        self.heal.is_some() || self.evolution
+    }
+
+    /// Restore the HP of `member`, and return how many it got back.
+    /// Creatures knocked out need a rest, not an item.
+    pub fn heal(&self, member: &mut TeamMember, dex: &Dex) -> u8 {
+        let Some(heal) = self.heal else {
+            return 0;
+        };
+        if member.is_ko() {
+            return 0;
+        }
+        let amount = heal.min(member.max_hp(dex).saturating_sub(member.hp));
+        member.hp += amount;
+        amount
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base_items_are_valid() {
+        let mut errors = Vec::new();
+        let catalog = ItemCatalog::parse(
+            "items.json",
+            include_str!("../../assets/items/items.json"),
+            &mut errors,
+        );
+        assert!(errors.is_empty(), "{errors:?}");
+        assert!(catalog.get("Potion").unwrap().usable_in_fight());
+        assert!(!catalog.get("Fire Stone").unwrap().usable_in_fight());
+    }
+
+    #[test]
+    fn faulty_items_are_reported_and_left_out() {
+        let content = r#"{ "items": [
+            { "name": "Potion", "description": "", "heal": 20 },
+            { "name": "Ether", "description": "", "heal": "a lot" },
+            { "name": "Empty", "description": "", "heal": 0 },
+            { "name": "Potion", "description": "", "colour": "red" }
+        ] }"#;
+        let mut errors = Vec::new();
+        let catalog = ItemCatalog::parse("items.json", content, &mut errors);
+        let paths: Vec<&str> = errors.iter().map(|error| error.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "items.json: items[1].heal",
+                "items.json: items[3].colour",
+                "items.json: items[2].heal",
+            ]
+        );
+        let names: Vec<&str> = catalog.0.iter().map(|item| item.name.as_str()).collect();
+        assert_eq!(names, ["Potion"]);
+    }
+}
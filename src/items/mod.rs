@@ -0,0 +1,93 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+mod catalog;
+mod systems;
+
+pub use catalog::{Item, ItemCatalog};
+use systems::*;
+
+use crate::AppState;
+
+/// Items carried by the player, by name, with how many of each.
+#[derive(Resource, Serialize, Deserialize, Clone)]
+pub struct Inventory(pub BTreeMap<String, u32>);
+
+/// Every new game starts with a few items. Older saves get them too.
+impl Default for Inventory {
+    fn default() -> Self {
+        Inventory(BTreeMap::from([
+            (String::from("Potion"), 3),
+            (String::from("Antidote"), 1),
+            (String::from("X Attack"), 1),
+            (String::from("Fire Stone"), 1),
+        ]))
+    }
+}
+
+impl Inventory {
+    pub fn count(&self, name: &str) -> u32 {
+        self.0.get(name).copied().unwrap_or_default()
+    }
+
+    /// Remove one item, and return whether there was one to remove.
+    pub fn take(&mut self, name: &str) -> bool {
+        let Some(left) = self.0.get(name).and_then(|count| count.checked_sub(1)) else {
+            return false;
+        };
+        if left == 0 {
+            self.0.remove(name);
+        } else {
+            self.0.insert(name.to_string(), left);
+        }
+        true
+    }
+}
+
+#[derive(Event)]
+/// Trigger when the player uses an item on a member of their team, outside fights.
+pub struct UseItemEvent {
+    pub item: String,
+    /// Index of the member in the `Team`.
+    pub member: usize,
+}
+
+pub struct ItemsPlugin;
+
+impl Plugin for ItemsPlugin {
+    fn build(&self, app: &mut bevy::app::App) {
+        app.init_resource::<Inventory>()
+            .insert_resource(ItemCatalog::new())
+            .add_event::<UseItemEvent>()
+            .add_systems(Update, use_item.run_if(in_state(AppState::InGame)))
+            .add_systems(
+                Update,
+                consume_used_items.run_if(in_state(AppState::InFight)),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn taking_the_last_item_removes_it() {
+        let mut inventory = Inventory(BTreeMap::from([(String::from("Potion"), 2)]));
+        assert!(inventory.take("Potion"));
+        assert_eq!(inventory.count("Potion"), 1);
+        assert!(inventory.take("Potion"));
+        assert!(!inventory.0.contains_key("Potion"));
+        assert!(!inventory.take("Potion"));
+    }
+
+    #[test]
+    fn nothing_is_taken_from_an_empty_entry() {
+        let mut inventory = Inventory(BTreeMap::from([(String::from("Potion"), 0)]));
+        assert!(!inventory.take("Potion"));
+        assert!(!inventory.take("Antidote"));
+        assert_eq!(inventory.count("Potion"), 0);
+    }
+}
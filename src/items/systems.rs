@@ -0,0 +1,56 @@
+use bevy::prelude::*;
+
+use crate::{
+    dex::Dex,
+    evolution::{PendingEvolution, PendingEvolutions},
+    fight::BattleEvent,
+    items::{Inventory, ItemCatalog, UseItemEvent},
+    taming::PlayerAura,
+    team::Team,
+};
+
+/// Use an item on a member of the team. It is only spent if it did something.
+/// Evolution items are spent once the player agrees to the evolution.
+pub fn use_item(
+    mut events: EventReader<UseItemEvent>,
+    mut inventory: ResMut<Inventory>,
+    mut team: ResMut<Team>,
+    mut pending: ResMut<PendingEvolutions>,
+    catalog: Res<ItemCatalog>,
+    aura: Res<PlayerAura>,
+    dex: Res<Dex>,
+) {
+    for event in events.read() {
+        let Some(item) = catalog.get(&event.item) else {
+            continue;
+        };
+        let Some(member) = team.0.get_mut(event.member) else {
+            continue;
+        };
+        if inventory.count(&item.name) == 0 {
+            continue;
+        }
+        if item.evolution
+            && let Some(into) = member.evolution(aura.0, Some(&item.name), &dex)
+        {
+            pending.ask(PendingEvolution {
+                member: event.member,
+                from: member.creature_id.clone(),
+                into,
+                item: Some(item.name.clone()),
+            });
+        }
+        if item.heal(member, &dex) > 0 {
+            inventory.take(&item.name);
+        }
+    }
+}
+
+/// Items used in a fight are gone once they reach their target.
+pub fn consume_used_items(mut events: EventReader<BattleEvent>, mut inventory: ResMut<Inventory>) {
+    for event in events.read() {
+        if let BattleEvent::ItemUsed { item, .. } = event {
+            inventory.take(item);
+        }
+    }
+}
@@ -2,9 +2,12 @@ mod animation;
 mod camera;
 mod dex;
 mod event;
+mod evolution;
 mod fight;
+mod items;
 mod player;
 mod save;
+mod taming;
 mod team;
 mod ui;
 mod utils;
@@ -20,8 +23,11 @@ use crate::animation::AnimationsPlugin;
 use crate::camera::{CamPlugin, WorldBundle};
 use crate::dex::DexPlugin;
 use crate::event::EventsPlugin;
+use crate::evolution::EvolutionPlugin;
 use crate::fight::FightPlugin;
+use crate::items::ItemsPlugin;
 use crate::player::PlayerPlugin;
+use crate::taming::TamingPlugin;
 use crate::ui::UiPlugin;
 use crate::world::WorldPlugin;
 
@@ -31,6 +37,8 @@ pub enum AppState {
     /// `MainMenu` is the initial state, when the main menu UI is displayed.
     #[default]
     MainMenu,
+    /// `NewGame` is when the player answers the questions giving them their aura, before a new game.
+    NewGame,
     /// `ResumeGame` is a preliminary state to `InGame`. It loads the save before running the game.
     ResumeGame,
     /// `InGame` is the state when we can play. The world, team and actions UIs are displayed.
@@ -67,6 +75,9 @@ fn main() {
         PlayerPlugin,
         AnimationsPlugin,
         FightPlugin,
+        TamingPlugin,
+        ItemsPlugin,
+        EvolutionPlugin,
     ));
     app.init_state::<AppState>();
 
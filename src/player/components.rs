@@ -1,5 +1,5 @@
 use bevy::prelude::*;
-use bevy_ecs_ldtk::{GridCoords, LdtkEntity};
+use bevy_ecs_ldtk::{GridCoords, LdtkEntity, Worldly};
 
 use crate::{animation::AnimationConfig, utils::Direction};
 
@@ -13,6 +13,9 @@ pub struct PlayerBundle {
     sprite_sheet: Sprite,
     #[grid_coords]
     grid_coords: GridCoords,
+    /// The player follows us from level to level.
+    #[worldly]
+    worldly: Worldly,
     direction: Direction,
     animation: AnimationConfig,
 }
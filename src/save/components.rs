@@ -4,21 +4,58 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 
 use super::SAVE_PATH;
-use crate::team::Team;
+use crate::{
+    dex::Aura,
+    items::Inventory,
+    taming::Rings,
+    team::{AwayMembers, Team},
+    world::RestPoint,
+};
 
 #[derive(Serialize, Deserialize, Resource)]
 pub struct Save {
     pub level: i32,
     pub coords: (i32, i32),
     pub team: Team,
+    /// Members waiting for their creature to be back in the dex.
+    #[serde(default)]
+    pub away: AwayMembers,
+    /// Older saves have none, the player then wakes up at the start of the game.
+    #[serde(default)]
+    pub rest_point: Option<RestPoint>,
+    /// Empty rings. Older saves get the rings of a new game.
+    #[serde(default)]
+    pub rings: Rings,
+    /// Aura of the player, from the questionnaire of the new game.
+    #[serde(default)]
+    pub aura: Aura,
+    /// Items carried. Older saves get the items of a new game.
+    #[serde(default)]
+    pub inventory: Inventory,
 }
 
 impl Save {
-    pub fn new(level: i32, coords: GridCoords, team: Team) {
+    /// Build a save from the current game and write it right away.
+    #[allow(clippy::new_ret_no_self, clippy::too_many_arguments)]
+    pub fn new(
+        level: i32,
+        coords: GridCoords,
+        team: Team,
+        away: AwayMembers,
+        rest_point: Option<RestPoint>,
+        rings: Rings,
+        aura: Aura,
+        inventory: Inventory,
+    ) {
         let save = Save {
             level,
             coords: (coords.x, coords.y),
             team,
+            away,
+            rest_point,
+            rings,
+            aura,
+            inventory,
         };
         save.write()
     }
@@ -63,25 +100,25 @@ impl Save {
             .is_some()
     }
 
+    /// Read a save, telling what is wrong with it otherwise.
+    pub fn from_json(content: &str) -> Result<Save, String> {
+        serde_json::from_str(content).map_err(|error| format!("faulty save: {error}"))
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
-    pub fn load() -> Option<Save> {
-        let content = fs::read_to_string(SAVE_PATH).ok()?;
-        let save: Save = serde_json::from_str(&content).unwrap();
-        Some(save)
+    pub fn load() -> Result<Save, String> {
+        let content = fs::read_to_string(SAVE_PATH)
+            .map_err(|error| format!("cannot read {SAVE_PATH}: {error}"))?;
+        Save::from_json(&content)
     }
 
     #[cfg(target_arch = "wasm32")]
-    pub fn load() -> Option<Save> {
+    pub fn load() -> Result<Save, String> {
         use web_sys::window;
         let content = window()
-            .unwrap()
-            .local_storage()
-            .unwrap()
-            .unwrap()
-            .get_item("pokeclone_save")
-            .unwrap()
-            .unwrap();
-        let save: Save = serde_json::from_str(&content).unwrap();
-        Some(save)
+            .and_then(|window| window.local_storage().ok().flatten())
+            .and_then(|storage| storage.get_item("pokeclone_save").ok().flatten())
+            .ok_or_else(|| String::from("no save in the local storage"))?;
+        Save::from_json(&content)
     }
 }
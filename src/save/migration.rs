@@ -0,0 +1,87 @@
+use serde::{Deserialize, Deserializer};
+
+use crate::dex::CreatureId;
+
+/// Ids of the creatures, in the order of `gen1.creatures.json` back when saves referred to them
+/// by position. It must not change.
+const LEGACY_CREATURE_IDS: [[&str; 4]; 3] = [
+    ["flammiaou", "stratocat", "matoulot", "chaterre"],
+    ["ratcendre", "mulovol", "hydrouris", "sourilex"],
+    ["hirondflamme", "piafoudre", "aqualet", "rocange"],
+];
+
+/// A creature as written in a save: its id, or the position of its species and of
+/// the individual in older saves.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SavedCreatureId {
+    Id(CreatureId),
+    Indices(usize, usize),
+}
+
+/// Read the id of a creature from a save, converting the positions of older saves.
+/// Positions out of the table get an id of their own, unknown to the dex: the member then
+/// waits out of the team like any other member of a missing creature.
+pub fn creature_id_or_indices<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<CreatureId, D::Error> {
+    Ok(match SavedCreatureId::deserialize(deserializer)? {
+        SavedCreatureId::Id(id) => id,
+        SavedCreatureId::Indices(species, individual) => LEGACY_CREATURE_IDS
+            .get(species)
+            .and_then(|individuals| individuals.get(individual))
+            .map(|id| CreatureId(id.to_string()))
+            .unwrap_or_else(|| CreatureId(format!("legacy_{species}_{individual}"))),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dex::Dex, save::Save, team::TeamMember};
+
+    #[test]
+    fn legacy_creatures_are_in_the_dex() {
+        let dex = Dex::from_base_files();
+        for id in LEGACY_CREATURE_IDS.iter().flatten() {
+            assert!(
+                dex.get_creature(&CreatureId(id.to_string())).is_some(),
+                "{id}"
+            );
+        }
+    }
+
+    #[test]
+    fn creatures_are_read_by_id_or_by_position() {
+        let read = |creature: &str| {
+            let json = format!(r#"{{ "surname": null, "creature_id": {creature}, "hp": 3 }}"#);
+            serde_json::from_str::<TeamMember>(&json).map(|member| member.creature_id.0)
+        };
+        assert_eq!(read(r#""mulovol""#).unwrap(), "mulovol");
+        assert_eq!(read("[1, 1]").unwrap(), "mulovol");
+        assert_eq!(read("[2, 3]").unwrap(), "rocange");
+        assert_eq!(read("[3, 0]").unwrap(), "legacy_3_0");
+        assert_eq!(read("[0, 4]").unwrap(), "legacy_0_4");
+        assert!(read(r#"{ "species": 1 }"#).is_err());
+    }
+
+    #[test]
+    fn legacy_saves_with_unknown_creatures_still_load() {
+        let dex = Dex::from_base_files();
+        let content = r#"{
+            "level": 0,
+            "coords": [3, 4],
+            "team": [
+                { "surname": null, "creature_id": [0, 0], "hp": 3 },
+                { "surname": null, "creature_id": [9, 9], "hp": 5 }
+            ]
+        }"#;
+        let mut save = Save::from_json(content).unwrap();
+        save.team.migrate(&mut save.away, &dex);
+        assert_eq!(save.team.0.len(), 1);
+        assert_eq!(save.team.0[0].creature_id.0, "flammiaou");
+        assert_eq!(save.away.0.len(), 1);
+        assert_eq!(save.away.0[0].creature_id.0, "legacy_9_9");
+        assert!(Save::from_json("{ \"level\": 0 }").is_err());
+    }
+}
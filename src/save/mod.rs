@@ -1,5 +1,7 @@
 mod components;
+mod migration;
 
 pub(crate) use components::Save;
+pub(crate) use migration::creature_id_or_indices;
 
 const SAVE_PATH: &str = "assets/saves/save.json";
@@ -0,0 +1,90 @@
+use std::fmt;
+
+use crate::{
+    dex::{Aura, Dex, Element},
+    fight::Battle,
+    taming::{Ring, TamingProgress},
+};
+
+/// Rounds a wise creature wants to spend with the player before joining them.
+const PATIENT_ROUNDS: u32 = 3;
+/// Different attacks a curious creature wants to see.
+const VARIED_ATTACKS: usize = 3;
+
+/// Something a wild creature expects from the fight before being willing to be tamed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TamingCondition {
+    /// The foe lost at least half of its HP.
+    Weakened,
+    /// The foe did not lose any HP.
+    Unhurt,
+    /// The player used several different attacks.
+    Varied,
+    /// The fight lasted a few rounds.
+    Patient,
+    /// The foe dodged an attack.
+    Dodged,
+    /// The foe suffers from a status.
+    Suffering,
+}
+
+impl TamingCondition {
+    /// What a creature of the given aura expects.
+    pub fn of(aura: Aura) -> [TamingCondition; 2] {
+        match aura {
+            Aura::Aggressive => [Self::Weakened, Self::Suffering],
+            Aura::Gentle => [Self::Unhurt, Self::Patient],
+            Aura::Curious => [Self::Varied, Self::Dodged],
+            Aura::Wise => [Self::Patient, Self::Varied],
+            Aura::Playful => [Self::Dodged, Self::Unhurt],
+            Aura::Reckless => [Self::Suffering, Self::Weakened],
+        }
+    }
+
+    /// The quality of the stone of the ring lowers the number of rounds and attacks expected.
+    pub fn is_met(
+        &self,
+        battle: &Battle,
+        foe: usize,
+        progress: &TamingProgress,
+        ring: &Ring,
+        dex: &Dex,
+    ) -> bool {
+        let combatant = &battle.combatants[foe];
+        let max_hp = combatant.member.max_hp(dex);
+        let bonus = ring.quality.bonus();
+        match self {
+            Self::Weakened => combatant.member.hp as u32 * 2 <= max_hp as u32,
+            Self::Unhurt => combatant.member.hp == max_hp,
+            Self::Varied => progress.attacks.len() >= VARIED_ATTACKS.saturating_sub(bonus as usize),
+            Self::Patient => progress.rounds >= PATIENT_ROUNDS.saturating_sub(bonus as u32),
+            Self::Dodged => progress.dodged,
+            Self::Suffering => combatant.effects.status().is_some(),
+        }
+    }
+}
+
+/// Hint given to the player when the condition is not met.
+impl fmt::Display for TamingCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Weakened => "to feel your strength",
+            Self::Unhurt => "to be treated kindly",
+            Self::Varied => "to see more of your attacks",
+            Self::Patient => "more time to know you",
+            Self::Dodged => "to play hide and seek",
+            Self::Suffering => "to be put to the test",
+        })
+    }
+}
+
+/// How many of its conditions a creature needs before being tamed.
+/// Sharing its aura makes it trust the player more, and the ring may spare some more,
+/// but a creature always expects at least one of them.
+pub fn required_conditions(aura: Aura, element: Element, player: Aura, ring: &Ring) -> usize {
+    TamingCondition::of(aura)
+        .len()
+        .saturating_sub((aura == player) as usize)
+        .saturating_sub(ring.spared_conditions(aura, element))
+        .max(1)
+}
@@ -0,0 +1,56 @@
+use bevy::{platform::collections::HashSet, prelude::*};
+
+mod conditions;
+mod questionnaire;
+mod rings;
+mod systems;
+
+pub use conditions::{TamingCondition, required_conditions};
+pub use questionnaire::Questionnaire;
+pub use rings::{Ring, Rings};
+use systems::*;
+
+use crate::{AppState, dex::Aura, fight::FightState};
+
+/// Aura of the player, it makes creatures sharing it easier to tame.
+#[derive(Resource, Default, Clone, Copy)]
+pub struct PlayerAura(pub Aura);
+
+/// Index of the answer given to each question of the `Questionnaire` so far.
+#[derive(Resource, Default)]
+pub struct QuestionnaireAnswers(pub Vec<usize>);
+
+/// What happened during a fight that a wild creature cares about.
+#[derive(Resource, Default)]
+pub struct TamingProgress {
+    /// Rounds played so far.
+    pub rounds: u32,
+    /// Names of the attacks used by the player's creatures.
+    pub attacks: HashSet<String>,
+    /// Whether the foe dodged an attack.
+    pub dodged: bool,
+}
+
+#[derive(Event)]
+/// Trigger when the player tries to tame the wild foe.
+pub struct TameAttemptEvent;
+
+pub struct TamingPlugin;
+
+impl Plugin for TamingPlugin {
+    fn build(&self, app: &mut bevy::app::App) {
+        app.init_resource::<PlayerAura>()
+            .init_resource::<Rings>()
+            .init_resource::<QuestionnaireAnswers>()
+            .insert_resource(Questionnaire::new())
+            .add_systems(OnEnter(AppState::NewGame), reset_questionnaire_answers)
+            .init_resource::<TamingProgress>()
+            .add_event::<TameAttemptEvent>()
+            .add_systems(OnEnter(AppState::InFight), reset_taming_progress)
+            .add_systems(OnEnter(FightState::Action), count_round)
+            .add_systems(
+                Update,
+                (track_taming_progress, attempt_taming).run_if(in_state(AppState::InFight)),
+            );
+    }
+}
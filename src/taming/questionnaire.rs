@@ -0,0 +1,174 @@
+use std::fmt;
+
+use bevy::{ecs::resource::Resource, platform::collections::HashMap};
+use serde::{
+    Deserialize, Deserializer,
+    de::{MapAccess, Visitor},
+};
+
+use crate::dex::{Aura, DexError, Entries, parse_file};
+
+/// Questions asked at the beginning of a new game to find the aura of the player.
+#[derive(Resource)]
+pub struct Questionnaire {
+    pub questions: Vec<Question>,
+}
+
+/// Schema of `questionnaire.json`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct QuestionnaireFile {
+    questions: Entries<Question>,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Question {
+    pub text: String,
+    pub answers: Vec<Answer>,
+}
+
+/// Each answer adds weights to some auras.
+#[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Answer {
+    pub text: String,
+    #[serde(deserialize_with = "weights")]
+    pub auras: Vec<(Aura, u32)>,
+}
+
+/// Read an object of weights by aura as a list, to keep the order of the answers.
+fn weights<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<(Aura, u32)>, D::Error> {
+    struct WeightsVisitor;
+
+    impl<'de> Visitor<'de> for WeightsVisitor {
+        type Value = Vec<(Aura, u32)>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("weights by aura")
+        }
+
+        fn visit_map<M: MapAccess<'de>>(self, mut map: M) -> Result<Self::Value, M::Error> {
+            let mut weights = Vec::new();
+            while let Some(entry) = map.next_entry()? {
+                weights.push(entry);
+            }
+            Ok(weights)
+        }
+    }
+
+    deserializer.deserialize_map(WeightsVisitor)
+}
+
+impl Questionnaire {
+    /// Read `questionnaire.json`. Faulty questions are reported and left out.
+    pub fn new() -> Self {
+        let content = include_str!("../../assets/taming/questionnaire.json");
+        let mut errors = Vec::new();
+        let questionnaire = Self::parse("questionnaire.json", content, &mut errors);
+        for error in errors.iter() {
+            eprintln!("{error}");
+        }
+        questionnaire
+    }
+
+    /// Read the questions of `content`, the errors going to `errors`.
+    /// A question without answers would leave the player stuck, it is left out.
+    pub fn parse(file: &str, content: &str, errors: &mut Vec<DexError>) -> Self {
+        let Some(questionnaire_file) = parse_file::<QuestionnaireFile>(file, content, errors)
+        else {
+            return Questionnaire { questions: vec![] };
+        };
+        let questions = questionnaire_file
+            .questions
+            .collect(&format!("{file}: questions"), errors)
+            .into_iter()
+            .filter(|(path, question)| {
+                if question.answers.is_empty() {
+                    errors.push(DexError::new(
+                        &format!("{path}.answers"),
+                        "should have at least one answer",
+                    ));
+                }
+                !question.answers.is_empty()
+            })
+            .map(|(_, question)| question.clone())
+            .collect();
+        Questionnaire { questions }
+    }
+
+    /// Aura with the most weight given the index of the answer to each question.
+    /// Ties go to the aura answered first.
+    pub fn aura(&self, answers: &[usize]) -> Aura {
+        let mut weights: HashMap<Aura, u32> = HashMap::new();
+        let mut order = Vec::new();
+        for (question, answer) in self.questions.iter().zip(answers) {
+            for (aura, weight) in question.answers[*answer].auras.iter() {
+                if !order.contains(aura) {
+                    order.push(*aura);
+                }
+                *weights.entry(*aura).or_default() += weight;
+            }
+        }
+        order
+            .into_iter()
+            .rev()
+            .max_by_key(|aura| weights[aura])
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base_questionnaire_is_valid() {
+        let mut errors = Vec::new();
+        let questionnaire = Questionnaire::parse(
+            "questionnaire.json",
+            include_str!("../../assets/taming/questionnaire.json"),
+            &mut errors,
+        );
+        assert!(errors.is_empty(), "{errors:?}");
+        assert!(!questionnaire.questions.is_empty());
+    }
+
+    #[test]
+    fn faulty_questions_are_reported_and_left_out() {
+        let content = r#"{ "questions": [
+            { "text": "Ok?", "answers": [{ "text": "Yes", "auras": { "Wise": 1 } }] },
+            { "text": "Nothing to say?", "answers": [] },
+            { "text": "Bad?", "answers": [{ "text": "Yes", "auras": { "Grumpy": 1 } }] }
+        ] }"#;
+        let mut errors = Vec::new();
+        let questionnaire = Questionnaire::parse("questionnaire.json", content, &mut errors);
+        let paths: Vec<&str> = errors.iter().map(|error| error.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "questionnaire.json: questions[2].answers[0].auras.Grumpy",
+                "questionnaire.json: questions[1].answers",
+            ]
+        );
+        assert_eq!(questionnaire.questions.len(), 1);
+    }
+
+    #[test]
+    fn ties_go_to_the_aura_answered_first() {
+        let content = r#"{ "questions": [
+            { "text": "1", "answers": [
+                { "text": "a", "auras": { "Wise": 1 } },
+                { "text": "b", "auras": { "Curious": 1 } }
+            ] },
+            { "text": "2", "answers": [
+                { "text": "a", "auras": { "Wise": 1 } },
+                { "text": "b", "auras": { "Curious": 1 } }
+            ] }
+        ] }"#;
+        let questionnaire = Questionnaire::parse("questionnaire.json", content, &mut Vec::new());
+        assert_eq!(questionnaire.aura(&[0, 1]), Aura::Wise);
+        assert_eq!(questionnaire.aura(&[1, 0]), Aura::Curious);
+        assert_eq!(questionnaire.aura(&[1, 1]), Aura::Curious);
+    }
+}
@@ -0,0 +1,127 @@
+use std::fmt;
+
+use bevy::ecs::resource::Resource;
+use serde::{Deserialize, Serialize};
+
+use crate::dex::{Aura, Element};
+
+/// Metal of a ring. Each one channels an aura better.
+#[derive(Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub enum Material {
+    Iron,
+    Brass,
+    Copper,
+    Pewter,
+    Silver,
+    Gold,
+}
+
+impl Material {
+    pub fn channels(&self) -> Aura {
+        match self {
+            Self::Iron => Aura::Aggressive,
+            Self::Brass => Aura::Reckless,
+            Self::Copper => Aura::Playful,
+            Self::Pewter => Aura::Curious,
+            Self::Silver => Aura::Wise,
+            Self::Gold => Aura::Gentle,
+        }
+    }
+}
+
+/// Stone set in a ring. Each one resonates with an element.
+#[derive(Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub enum Stone {
+    Ruby,
+    Topaz,
+    Emerald,
+    Sapphire,
+}
+
+impl Stone {
+    pub fn element(&self) -> Element {
+        match self {
+            Self::Ruby => Element::Fire,
+            Self::Topaz => Element::Air,
+            Self::Emerald => Element::Earth,
+            Self::Sapphire => Element::Water,
+        }
+    }
+}
+
+/// The better the stone, the less a creature asks for before joining the player.
+#[derive(Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub enum Quality {
+    Rough,
+    Polished,
+    Flawless,
+}
+
+impl Quality {
+    /// How much the thresholds of the conditions are lowered.
+    pub fn bonus(&self) -> u8 {
+        match self {
+            Self::Rough => 0,
+            Self::Polished => 1,
+            Self::Flawless => 2,
+        }
+    }
+}
+
+/// A tamed creature lives in a stone ring. The player needs an empty one to tame a creature.
+#[derive(Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct Ring {
+    pub material: Material,
+    pub stone: Stone,
+    pub quality: Quality,
+}
+
+impl Ring {
+    /// How many conditions the ring spares when taming a creature of the given aura and element.
+    pub fn spared_conditions(&self, aura: Aura, element: Element) -> usize {
+        (self.material.channels() == aura) as usize + (self.stone.element() == element) as usize
+    }
+}
+
+impl fmt::Display for Ring {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} {:?} ring ({:?})",
+            self.material, self.stone, self.quality
+        )
+    }
+}
+
+/// Empty rings carried by the player. The team cannot grow beyond the rings, at most `Team::MAX`.
+#[derive(Resource, Serialize, Deserialize, Clone)]
+pub struct Rings(pub Vec<Ring>);
+
+impl Default for Rings {
+    /// A new game starts with a few plain rings.
+    fn default() -> Self {
+        let ring = |stone| Ring {
+            material: Material::Iron,
+            stone,
+            quality: Quality::Rough,
+        };
+        Rings(vec![
+            ring(Stone::Ruby),
+            ring(Stone::Sapphire),
+            ring(Stone::Emerald),
+        ])
+    }
+}
+
+impl Rings {
+    /// Index of the empty ring sparing the most conditions, then with the best stone,
+    /// then the first one.
+    pub fn best_for(&self, aura: Aura, element: Element) -> Option<usize> {
+        self.0
+            .iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|(_, ring)| (ring.spared_conditions(aura, element), ring.quality.bonus()))
+            .map(|(idx, _)| idx)
+    }
+}
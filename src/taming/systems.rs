@@ -0,0 +1,377 @@
+use bevy::prelude::*;
+
+use crate::{
+    dex::{Aura, Dex},
+    fight::{Battle, BattleEvent, BattleLog, FightState, Side},
+    taming::{
+        PlayerAura, QuestionnaireAnswers, Ring, Rings, TameAttemptEvent, TamingCondition,
+        TamingProgress, required_conditions,
+    },
+    team::Team,
+};
+
+/// Every new game starts from scratch.
+pub fn reset_questionnaire_answers(mut answers: ResMut<QuestionnaireAnswers>) {
+    answers.0.clear();
+}
+
+/// Every fight starts from scratch.
+pub fn reset_taming_progress(mut progress: ResMut<TamingProgress>) {
+    *progress = TamingProgress::default();
+}
+
+pub fn count_round(mut progress: ResMut<TamingProgress>) {
+    progress.rounds += 1;
+}
+
+/// Remember what the foe saw during the fight.
+pub fn track_taming_progress(
+    mut events: EventReader<BattleEvent>,
+    mut progress: ResMut<TamingProgress>,
+    battle: Option<Res<Battle>>,
+) {
+    let Some(battle) = battle else {
+        return;
+    };
+    for event in events.read() {
+        match event {
+            BattleEvent::AttackUsed { source, attack }
+                if battle.combatants[*source].side == Side::Player =>
+            {
+                progress.attacks.insert(attack.clone());
+            }
+            BattleEvent::Missed { target, .. } if battle.combatants[*target].side == Side::Foe => {
+                progress.dodged = true;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// What came out of an attempt to tame the wild foe.
+#[derive(Debug)]
+pub enum TamingOutcome {
+    /// The foe joined the team, living in this ring.
+    Joined(Ring),
+    /// The foe is not willing yet, it wants this condition to be met.
+    Refused(TamingCondition),
+    TeamFull,
+    NoRing,
+}
+
+/// The wild `foe` joins the team if enough of the conditions of its aura are met, and moves
+/// into the best empty ring for it. Otherwise, the team and the rings stay as they are.
+pub fn tame(
+    battle: &Battle,
+    foe: usize,
+    team: &mut Team,
+    rings: &mut Rings,
+    progress: &TamingProgress,
+    player_aura: Aura,
+    dex: &Dex,
+) -> TamingOutcome {
+    let member = &battle.combatants[foe].member;
+    if team.0.len() >= Team::MAX {
+        return TamingOutcome::TeamFull;
+    }
+    let creature = member.creature(dex);
+    let (aura, element) = (creature.aura, creature.element);
+    let Some(slot) = rings.best_for(aura, element) else {
+        return TamingOutcome::NoRing;
+    };
+    let ring = rings.0[slot];
+    let (met, missing): (Vec<TamingCondition>, Vec<TamingCondition>) = TamingCondition::of(aura)
+        .into_iter()
+        .partition(|condition| condition.is_met(battle, foe, progress, &ring, dex));
+    if met.len() >= required_conditions(aura, element, player_aura, &ring) {
+        let mut member = member.clone();
+        member.ring = Some(rings.0.remove(slot));
+        team.0.push(member);
+        TamingOutcome::Joined(ring)
+    } else {
+        TamingOutcome::Refused(missing[0])
+    }
+}
+
+/// Try to tame the wild foe, the player's turn is lost when it is not willing yet.
+#[allow(clippy::too_many_arguments)]
+pub fn attempt_taming(
+    mut events: EventReader<TameAttemptEvent>,
+    battle: Option<Res<Battle>>,
+    mut team: ResMut<Team>,
+    mut rings: ResMut<Rings>,
+    mut log: ResMut<BattleLog>,
+    progress: Res<TamingProgress>,
+    player_aura: Res<PlayerAura>,
+    dex: Res<Dex>,
+    mut next_fight_state: ResMut<NextState<FightState>>,
+) {
+    let Some(battle) = battle else {
+        return;
+    };
+    for _ in events.read() {
+        let Some(foe) = battle.active(Side::Foe) else {
+            continue;
+        };
+        let name = battle.combatants[foe].member.name(&dex);
+        match tame(
+            &battle,
+            foe,
+            &mut team,
+            &mut rings,
+            &progress,
+            player_aura.0,
+            &dex,
+        ) {
+            TamingOutcome::Joined(ring) => {
+                log.0
+                    .push(format!("{name} joins your team in your {ring}!"));
+                next_fight_state.set(FightState::Tamed);
+            }
+            TamingOutcome::Refused(missing) => {
+                log.0
+                    .push(format!("{name} is not willing yet, it wants {missing}."));
+                next_fight_state.set(FightState::EnemyTurn);
+            }
+            TamingOutcome::TeamFull => log
+                .0
+                .push(format!("Your team is full, {name} cannot join it.")),
+            TamingOutcome::NoRing => log.0.push(format!("You have no empty ring for {name}.")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        dex::CreatureId,
+        fight::AiKind,
+        taming::rings::{Material, Quality, Ring, Stone},
+        team::TeamMember,
+    };
+
+    fn member(id: &str, dex: &Dex) -> TeamMember {
+        let creature = &dex.get_creature(&CreatureId(id.to_string())).unwrap().0;
+        TeamMember::new(creature, dex)
+    }
+
+    /// A wild fight of flammiaou against `foe`, 0 being the player's creature and 1 the foe.
+    fn battle(foe: &str, dex: &Dex) -> (Team, Battle) {
+        let team = Team(vec![member("flammiaou", dex)]);
+        let battle = Battle::new(&team, &[0], vec![member(foe, dex)], 1, AiKind::Random);
+        (team, battle)
+    }
+
+    fn ring(material: Material, stone: Stone, quality: Quality) -> Ring {
+        Ring {
+            material,
+            stone,
+            quality,
+        }
+    }
+
+    /// A rough iron ruby ring, which does not help with the foes of these tests.
+    fn plain_rings() -> Rings {
+        Rings(vec![ring(Material::Iron, Stone::Ruby, Quality::Rough)])
+    }
+
+    fn aura_of(id: &str, dex: &Dex) -> (Aura, crate::dex::Element) {
+        let creature = &dex.get_creature(&CreatureId(id.to_string())).unwrap().0;
+        (creature.aura, creature.element)
+    }
+
+    #[test]
+    fn conditions_follow_the_fight() {
+        let dex = Dex::from_base_files();
+        let (_, mut battle) = battle("mulovol", &dex);
+        let ring = plain_rings().0[0];
+        let mut progress = TamingProgress::default();
+        let met = |condition: TamingCondition, battle: &Battle, progress: &TamingProgress| {
+            condition.is_met(battle, 1, progress, &ring, &dex)
+        };
+        assert!(met(TamingCondition::Unhurt, &battle, &progress));
+        assert!(!met(TamingCondition::Weakened, &battle, &progress));
+        assert!(!met(TamingCondition::Patient, &battle, &progress));
+        assert!(!met(TamingCondition::Dodged, &battle, &progress));
+        assert!(!met(TamingCondition::Suffering, &battle, &progress));
+        let max_hp = battle.combatants[1].member.max_hp(&dex);
+        battle.combatants[1].member.hp = max_hp / 2;
+        battle.combatants[1]
+            .effects
+            .inflict(crate::dex::Status::Poison, 3);
+        progress.rounds = 3;
+        progress.dodged = true;
+        assert!(!met(TamingCondition::Unhurt, &battle, &progress));
+        assert!(met(TamingCondition::Weakened, &battle, &progress));
+        assert!(met(TamingCondition::Patient, &battle, &progress));
+        assert!(met(TamingCondition::Dodged, &battle, &progress));
+        assert!(met(TamingCondition::Suffering, &battle, &progress));
+    }
+
+    #[test]
+    fn better_stones_ask_for_less() {
+        let dex = Dex::from_base_files();
+        let (_, battle) = battle("mulovol", &dex);
+        let progress = TamingProgress {
+            rounds: 1,
+            ..Default::default()
+        };
+        let flawless = ring(Material::Iron, Stone::Ruby, Quality::Flawless);
+        assert!(TamingCondition::Patient.is_met(&battle, 1, &progress, &flawless, &dex));
+        assert!(!TamingCondition::Varied.is_met(&battle, 1, &progress, &flawless, &dex));
+    }
+
+    #[test]
+    fn at_least_one_condition_is_always_required() {
+        let (aura, element) = (Aura::Gentle, crate::dex::Element::Fire);
+        let helping = ring(Material::Gold, Stone::Ruby, Quality::Flawless);
+        assert_eq!(
+            required_conditions(aura, element, Aura::Aggressive, &helping),
+            1
+        );
+        assert_eq!(
+            required_conditions(aura, element, Aura::Gentle, &helping),
+            1
+        );
+        let plain = ring(Material::Iron, Stone::Sapphire, Quality::Rough);
+        assert_eq!(
+            required_conditions(aura, element, Aura::Aggressive, &plain),
+            2
+        );
+        assert_eq!(required_conditions(aura, element, Aura::Gentle, &plain), 1);
+    }
+
+    #[test]
+    fn rings_helping_the_most_are_chosen_first() {
+        let (aura, element) = (Aura::Gentle, crate::dex::Element::Fire);
+        let rings = Rings(vec![
+            ring(Material::Iron, Stone::Sapphire, Quality::Rough),
+            ring(Material::Gold, Stone::Sapphire, Quality::Rough),
+            ring(Material::Iron, Stone::Ruby, Quality::Polished),
+            ring(Material::Iron, Stone::Ruby, Quality::Polished),
+        ]);
+        // same spared conditions: the best stone, then the first ring
+        assert_eq!(rings.best_for(aura, element), Some(2));
+        let rings = Rings(vec![
+            ring(Material::Iron, Stone::Sapphire, Quality::Flawless),
+            ring(Material::Gold, Stone::Ruby, Quality::Rough),
+        ]);
+        // sparing a condition beats a better stone
+        assert_eq!(rings.best_for(aura, element), Some(1));
+        assert_eq!(Rings(vec![]).best_for(aura, element), None);
+    }
+
+    #[test]
+    fn tamed_foes_join_the_team_in_a_ring() {
+        let dex = Dex::from_base_files();
+        let (mut team, mut battle) = battle("mulovol", &dex);
+        let (aura, _) = aura_of("mulovol", &dex);
+        // meet every condition the foe may expect
+        battle.combatants[1]
+            .effects
+            .inflict(crate::dex::Status::Poison, 3);
+        let progress = TamingProgress {
+            rounds: 10,
+            attacks: ["a", "b", "c"].map(String::from).into_iter().collect(),
+            dodged: true,
+        };
+        let hp = battle.combatants[1].member.max_hp(&dex);
+        let conditions = TamingCondition::of(aura);
+        if conditions.contains(&TamingCondition::Weakened) {
+            battle.combatants[1].member.hp = hp / 2;
+        }
+        let mut rings = plain_rings();
+        let outcome = tame(&battle, 1, &mut team, &mut rings, &progress, aura, &dex);
+        assert!(matches!(outcome, TamingOutcome::Joined(_)), "{outcome:?}");
+        assert!(rings.0.is_empty());
+        assert_eq!(team.0.len(), 2);
+        assert_eq!(team.0[1].creature_id.0, "mulovol");
+        assert!(team.0[1].ring.is_some());
+    }
+
+    #[test]
+    fn unwilling_foes_leave_the_ring_empty() {
+        let dex = Dex::from_base_files();
+        let (mut team, battle) = battle("mulovol", &dex);
+        let (aura, _) = aura_of("mulovol", &dex);
+        let other = [
+            Aura::Aggressive,
+            Aura::Gentle,
+            Aura::Curious,
+            Aura::Wise,
+            Aura::Playful,
+            Aura::Reckless,
+        ]
+        .into_iter()
+        .find(|other| *other != aura)
+        .unwrap();
+        let mut rings = plain_rings();
+        let outcome = tame(
+            &battle,
+            1,
+            &mut team,
+            &mut rings,
+            &TamingProgress::default(),
+            other,
+            &dex,
+        );
+        assert!(matches!(outcome, TamingOutcome::Refused(_)), "{outcome:?}");
+        assert_eq!(rings.0.len(), 1);
+        assert_eq!(team.0.len(), 1);
+    }
+
+    #[test]
+    fn taming_needs_an_empty_ring() {
+        let dex = Dex::from_base_files();
+        let (mut team, battle) = battle("mulovol", &dex);
+        let (aura, _) = aura_of("mulovol", &dex);
+        let mut rings = Rings(vec![]);
+        let progress = TamingProgress {
+            dodged: true,
+            ..Default::default()
+        };
+        let outcome = tame(&battle, 1, &mut team, &mut rings, &progress, aura, &dex);
+        assert!(matches!(outcome, TamingOutcome::NoRing), "{outcome:?}");
+        assert_eq!(team.0.len(), 1);
+    }
+
+    #[test]
+    fn full_teams_keep_their_rings() {
+        let dex = Dex::from_base_files();
+        let (mut team, battle) = battle("mulovol", &dex);
+        team.0 = vec![member("flammiaou", &dex); Team::MAX];
+        let (aura, _) = aura_of("mulovol", &dex);
+        let mut rings = plain_rings();
+        let progress = TamingProgress {
+            dodged: true,
+            ..Default::default()
+        };
+        let outcome = tame(&battle, 1, &mut team, &mut rings, &progress, aura, &dex);
+        assert!(matches!(outcome, TamingOutcome::TeamFull), "{outcome:?}");
+        assert_eq!(rings.0.len(), 1);
+        assert_eq!(team.0.len(), Team::MAX);
+    }
+
+    #[test]
+    fn failed_taming_keeps_the_helping_ring() {
+        let dex = Dex::from_base_files();
+        let (mut team, mut battle) = battle("mulovol", &dex);
+        // a playful air creature, hurt and never dodging: none of its conditions is met
+        battle.combatants[1].member.hp -= 1;
+        let helping = ring(Material::Copper, Stone::Topaz, Quality::Flawless);
+        let mut rings = Rings(vec![plain_rings().0[0], helping]);
+        let outcome = tame(
+            &battle,
+            1,
+            &mut team,
+            &mut rings,
+            &TamingProgress::default(),
+            Aura::Playful,
+            &dex,
+        );
+        assert!(matches!(outcome, TamingOutcome::Refused(_)), "{outcome:?}");
+        assert_eq!(rings.0, [plain_rings().0[0], helping]);
+        assert_eq!(team.0.len(), 1);
+    }
+}
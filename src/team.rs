@@ -1,41 +1,465 @@
-use bevy::ecs::resource::Resource;
+use bevy::{
+    ecs::resource::Resource,
+    log::{info, warn},
+};
 use serde::{Deserialize, Serialize};
 
-use crate::dex::Dex;
+use crate::{
+    dex::{AttackId, Aura, Creature, CreatureId, Dex, Element, Stats},
+    save::creature_id_or_indices,
+    taming::Ring,
+};
+
+/// Members stop growing at this level.
+pub const MAX_LEVEL: u8 = 20;
+/// Members know up to this many attacks at once.
+pub const MAX_MOVES: usize = 4;
+/// Each level adds this percentage of the species base to HP, attack, defense and speed.
+const GROWTH_PERCENT: u32 = 5;
+/// XP needed to reach the next level, times the current level.
+const XP_PER_LEVEL: u32 = 20;
+/// XP given by a defeated foe, times its level.
+const XP_PER_FOE_LEVEL: u32 = 15;
 
 #[derive(Resource, Serialize, Deserialize, Clone)]
 pub struct Team(pub Vec<TeamMember>);
 
 impl Team {
-    pub fn new() -> Self {
-        Team(Vec::new())
+    /// The player carries up to five creatures.
+    pub const MAX: usize = 5;
+
+    /// Whether at least one member is able to fight.
+    pub fn can_fight(&self) -> bool {
+        self.0.iter().any(|member| !member.is_ko())
+    }
+
+    /// Bring the members of a save up to date. Members of creatures missing from the dex,
+    /// such as the ones of a pack turned off, wait in `away`; the ones waiting there come
+    /// back once their creature is known again and the team has room. Attacks saved by name
+    /// get their id, and members saved before move sets existed get new ones.
+    pub fn migrate(&mut self, away: &mut AwayMembers, dex: &Dex) {
+        let in_team = self.0.len();
+        let members: Vec<TeamMember> = self.0.drain(..).chain(away.0.drain(..)).collect();
+        for (idx, member) in members.into_iter().enumerate() {
+            let known = dex.get_creature(&member.creature_id).is_some();
+            if known && self.0.len() < Team::MAX {
+                if idx >= in_team {
+                    info!("{} is back in the team", member.creature_id);
+                }
+                self.0.push(member);
+            } else {
+                if !known {
+                    warn!(
+                        "Unknown creature {}, kept out of the team until it is back",
+                        member.creature_id
+                    );
+                }
+                away.0.push(member);
+            }
+        }
+        for member in self.0.iter_mut() {
+            member.moves = member
+                .moves
+                .iter()
+                .filter_map(|id| {
+                    dex.get_attack(id)
+                        .or_else(|| dex.attacks.0.iter().find(|atk| atk.name() == id.0).cloned())
+                })
+                .map(|atk| atk.id())
+                .collect();
+            if member.moves.is_empty() {
+                member.moves = dex.initial_moves(member.creature(dex), member.level);
+            }
+        }
+    }
+
+    /// Bring every member back to full health.
+    pub fn heal(&mut self, dex: &Dex) {
+        for member in self.0.iter_mut() {
+            member.hp = member.max_hp(dex);
+        }
     }
 }
 
+/// Members of the team whose creature is missing from the dex, such as the ones of a pack
+/// turned off or left out. They are saved, and come back with their creature.
+#[derive(Resource, Serialize, Deserialize, Clone, Default)]
+pub struct AwayMembers(pub Vec<TeamMember>);
+
 /// A team member is different from a creature. it shares a lot with a creature, but
 /// at the end of the day it has an additional state which is its current health points.
 /// It should also have a list of active effects for the fight, and eventually cross fight effects like statuses.
 #[derive(Clone, Serialize, Deserialize)]
 pub struct TeamMember {
     surname: Option<String>,
-    pub creature_id: (usize, usize),
+    /// Saves made before ids existed refer to creatures by their position in the dex.
+    #[serde(deserialize_with = "creature_id_or_indices")]
+    pub creature_id: CreatureId,
     pub hp: u8,
+    /// Saves made before levels existed start at level 1.
+    #[serde(default = "first_level")]
+    pub level: u8,
+    /// XP gained since the last level up.
+    #[serde(default)]
+    pub xp: u32,
+    /// Stone ring the member lives in. Wild creatures have none.
+    #[serde(default)]
+    pub ring: Option<Ring>,
+    /// Attacks it knows, up to `MAX_MOVES`.
+    #[serde(default)]
+    pub moves: Vec<AttackId>,
+}
+
+fn first_level() -> u8 {
+    1
 }
 
 impl TeamMember {
+    /// Create a level 1 team member at full health, knowing its first attacks.
+    pub fn new(creature: &Creature, dex: &Dex) -> Self {
+        TeamMember::at_level(creature, 1, dex)
+    }
+
+    /// Create a team member at full health at `level`, knowing the attacks learnt up to it.
+    pub fn at_level(creature: &Creature, level: u8, dex: &Dex) -> Self {
+        let level = level.clamp(1, MAX_LEVEL);
+        let mut member = TeamMember {
+            surname: None,
+            creature_id: creature.id.clone(),
+            hp: 0,
+            level,
+            xp: 0,
+            ring: None,
+            moves: dex.initial_moves(creature, level),
+        };
+        member.hp = member.max_hp(dex);
+        member
+    }
+
+    /// Creature of the member. Saves are checked when loaded, members are always in the dex.
+    pub fn creature<'a>(&self, dex: &'a Dex) -> &'a Creature {
+        &dex.get_creature(&self.creature_id)
+            .unwrap_or_else(|| panic!("unknown creature {}", self.creature_id))
+            .0
+    }
+
+    /// Learn an attack of the compatible pool again, forgetting the one at `forget` when
+    /// there is no room left. Return whether it was learnt.
+    pub fn relearn(&mut self, attack: &AttackId, forget: Option<usize>, dex: &Dex) -> bool {
+        let compatible = dex
+            .filter_attacks_for_team_member(self.clone())
+            .into_iter()
+            .any(|atk| atk.id() == *attack);
+        if !compatible || self.moves.contains(attack) {
+            return false;
+        }
+        match forget {
+            Some(slot) if slot < self.moves.len() => self.moves[slot] = attack.clone(),
+            _ if self.moves.len() < MAX_MOVES => self.moves.push(attack.clone()),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Creature it evolves into when `item` is used on it, or after gaining XP when there is
+    /// no item. `aura` is the one of the player.
+    pub fn evolution(&self, aura: Aura, item: Option<&str>, dex: &Dex) -> Option<CreatureId> {
+        self.creature(dex)
+            .evolutions
+            .iter()
+            .find(|evolution| evolution.is_triggered(self.level, aura, item))
+            .map(|evolution| evolution.into.clone())
+    }
+
+    /// Turn into another creature. The stats follow the new one, the damage taken stays,
+    /// and the nickname, level and moves are kept.
+    pub fn evolve(&mut self, into: &CreatureId, dex: &Dex) {
+        let damage = self.max_hp(dex).saturating_sub(self.hp);
+        let ko = self.is_ko();
+        self.creature_id = into.clone();
+        self.hp = if ko {
+            0
+        } else {
+            self.max_hp(dex).saturating_sub(damage).max(1)
+        };
+    }
+
     pub fn name(&self, dex: &Dex) -> String {
         self.surname
             .clone()
-            .unwrap_or(dex.get_creature(self.creature_id).0.name.clone())
+            .unwrap_or(self.creature(dex).name.clone())
+    }
+
+    pub fn is_ko(&self) -> bool {
+        self.hp == 0
     }
 
     pub fn texture_path(&self, dex: &Dex) -> String {
-        dex.get_creature(self.creature_id).0.assets_texture_path()
+        self.creature(dex).assets_texture_path()
     }
 
     pub fn max_hp(&self, dex: &Dex) -> u8 {
-        dex.get_creature(self.creature_id).0.stats.hp
+        self.stats(dex).hp
+    }
+
+    pub fn element(&self, dex: &Dex) -> Element {
+        self.creature(dex).element
     }
 
-    // todo base stat + actual stats (from combat)
+    /// Stats of the member, out of any fight effect: the species base grown with the level.
+    /// Dodge and accuracy are percentages and do not grow.
+    pub fn stats(&self, dex: &Dex) -> Stats {
+        let base = self.creature(dex).stats.clone();
+        let grow = |value: u8| {
+            let percent = 100 + GROWTH_PERCENT * (self.level.max(1) as u32 - 1);
+            (value as u32 * percent / 100).min(u8::MAX as u32) as u8
+        };
+        Stats {
+            hp: grow(base.hp),
+            attack: grow(base.attack),
+            defense: grow(base.defense),
+            speed: grow(base.speed),
+            ..base
+        }
+    }
+
+    /// XP needed to reach the next level, `None` at the max level.
+    pub fn xp_to_next_level(&self) -> Option<u32> {
+        (self.level < MAX_LEVEL).then_some(XP_PER_LEVEL * self.level as u32)
+    }
+
+    /// XP earned for defeating this member.
+    pub fn xp_reward(&self) -> u32 {
+        XP_PER_FOE_LEVEL * self.level as u32
+    }
+
+    /// Add XP and level up as much as needed. The HP gained with the levels are healed.
+    /// Return the levels reached.
+    pub fn gain_xp(&mut self, amount: u32, dex: &Dex) -> Vec<u8> {
+        let mut reached = Vec::new();
+        self.xp += amount;
+        while let Some(needed) = self.xp_to_next_level()
+            && self.xp >= needed
+        {
+            let max_hp = self.max_hp(dex);
+            self.xp -= needed;
+            self.level += 1;
+            self.hp = self.hp.saturating_add(self.max_hp(dex) - max_hp);
+            reached.push(self.level);
+        }
+        if self.level >= MAX_LEVEL {
+            self.xp = 0;
+        }
+        reached
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn creature<'a>(id: &str, dex: &'a Dex) -> &'a Creature {
+        &dex.get_creature(&CreatureId(id.to_string())).unwrap().0
+    }
+
+    #[test]
+    fn migration_gives_ids_to_attacks_saved_by_name() {
+        let dex = Dex::from_base_files();
+        let mut member = TeamMember::new(creature("flammiaou", &dex), &dex);
+        member.moves = vec![
+            AttackId(String::from("Scratch")),
+            AttackId(String::from("kick")),
+        ];
+        let mut team = Team(vec![member]);
+        team.migrate(&mut AwayMembers::default(), &dex);
+        assert_eq!(
+            team.0[0].moves,
+            [
+                AttackId(String::from("scratch")),
+                AttackId(String::from("kick"))
+            ]
+        );
+    }
+
+    #[test]
+    fn members_of_missing_creatures_wait_until_they_are_back() {
+        let dex = Dex::from_base_files();
+        let mut lost = TeamMember::new(creature("flammiaou", &dex), &dex);
+        lost.creature_id = CreatureId(String::from("example_lizard_fire"));
+        lost.level = 7;
+        let mut team = Team(vec![
+            TeamMember::new(creature("mulovol", &dex), &dex),
+            lost.clone(),
+        ]);
+        let mut away = AwayMembers::default();
+        team.migrate(&mut away, &dex);
+        assert_eq!(team.0.len(), 1);
+        assert_eq!(away.0.len(), 1);
+        assert_eq!(away.0[0].level, 7);
+
+        // the pack is back
+        away.0[0].creature_id = CreatureId(String::from("stratocat"));
+        team.migrate(&mut away, &dex);
+        assert_eq!(team.0.len(), 2);
+        assert_eq!(team.0[1].level, 7);
+        assert!(away.0.is_empty());
+    }
+
+    #[test]
+    fn members_coming_back_wait_for_room_in_the_team() {
+        let dex = Dex::from_base_files();
+        let member = TeamMember::new(creature("mulovol", &dex), &dex);
+        let mut team = Team(vec![member.clone(); Team::MAX]);
+        let mut away = AwayMembers(vec![member]);
+        team.migrate(&mut away, &dex);
+        assert_eq!(team.0.len(), Team::MAX);
+        assert_eq!(away.0.len(), 1);
+    }
+
+    #[test]
+    fn migration_gives_moves_to_members_without_any() {
+        let dex = Dex::from_base_files();
+        let mut member = TeamMember::new(creature("flammiaou", &dex), &dex);
+        member.moves = vec![AttackId(String::from("forgotten"))];
+        let mut team = Team(vec![member]);
+        team.migrate(&mut AwayMembers::default(), &dex);
+        assert!(!team.0[0].moves.is_empty());
+        assert!(team.0[0].moves.len() <= MAX_MOVES);
+        assert!(
+            team.0[0]
+                .moves
+                .iter()
+                .all(|id| dex.get_attack(id).is_some())
+        );
+    }
+
+    #[test]
+    fn evolutions_need_their_level_aura_and_item() {
+        let dex = Dex::from_base_files();
+        let mut ratcendre = TeamMember::at_level(creature("ratcendre", &dex), 9, &dex);
+        assert_eq!(ratcendre.evolution(Aura::Gentle, None, &dex), None);
+        ratcendre.level = 10;
+        assert_eq!(
+            ratcendre.evolution(Aura::Gentle, None, &dex),
+            Some(CreatureId(String::from("flammiaou")))
+        );
+        assert_eq!(
+            ratcendre.evolution(Aura::Gentle, Some("Fire Stone"), &dex),
+            None
+        );
+
+        let sourilex = TeamMember::at_level(creature("sourilex", &dex), 8, &dex);
+        assert_eq!(sourilex.evolution(Aura::Gentle, None, &dex), None);
+        assert_eq!(
+            sourilex.evolution(Aura::Wise, None, &dex),
+            Some(CreatureId(String::from("chaterre")))
+        );
+
+        let piafoudre = TeamMember::new(creature("piafoudre", &dex), &dex);
+        assert_eq!(piafoudre.evolution(Aura::Gentle, None, &dex), None);
+        assert_eq!(
+            piafoudre.evolution(Aura::Gentle, Some("Air Stone"), &dex),
+            None
+        );
+        assert_eq!(
+            piafoudre.evolution(Aura::Gentle, Some("Fire Stone"), &dex),
+            Some(CreatureId(String::from("hirondflamme")))
+        );
+    }
+
+    #[test]
+    fn evolving_keeps_the_damage_level_and_moves() {
+        let dex = Dex::from_base_files();
+        let mut member = TeamMember::at_level(creature("ratcendre", &dex), 10, &dex);
+        member.hp -= 5;
+        let moves = member.moves.clone();
+        member.evolve(&CreatureId(String::from("flammiaou")), &dex);
+        assert_eq!(member.creature_id.0, "flammiaou");
+        assert_eq!(member.hp, member.max_hp(&dex) - 5);
+        assert_eq!(member.level, 10);
+        assert_eq!(member.moves, moves);
+
+        member.hp = 0;
+        member.evolve(&CreatureId(String::from("stratocat")), &dex);
+        assert!(member.is_ko());
+    }
+
+    #[test]
+    fn members_are_created_at_full_health_of_their_level() {
+        let dex = Dex::from_base_files();
+        let first = TeamMember::new(creature("flammiaou", &dex), &dex);
+        let member = TeamMember::at_level(creature("flammiaou", &dex), 9, &dex);
+        assert_eq!(member.level, 9);
+        assert_eq!(member.hp, member.max_hp(&dex));
+        assert!(member.max_hp(&dex) > first.max_hp(&dex));
+        assert!(member.moves.len() >= first.moves.len());
+        let too_high = TeamMember::at_level(creature("flammiaou", &dex), 99, &dex);
+        assert_eq!(too_high.level, MAX_LEVEL);
+    }
+
+    #[test]
+    fn xp_levels_up_and_heals_the_hp_gained() {
+        let dex = Dex::from_base_files();
+        let mut member = TeamMember::new(creature("flammiaou", &dex), &dex);
+        member.hp -= 1;
+        // 20 XP to reach level 2, 40 more to reach level 3.
+        assert_eq!(member.gain_xp(65, &dex), vec![2, 3]);
+        assert_eq!(member.level, 3);
+        assert_eq!(member.xp, 5);
+        assert_eq!(member.hp, member.max_hp(&dex) - 1);
+    }
+
+    #[test]
+    fn xp_stops_at_the_max_level() {
+        let dex = Dex::from_base_files();
+        let mut member = TeamMember::at_level(creature("flammiaou", &dex), MAX_LEVEL - 1, &dex);
+        assert_eq!(member.gain_xp(10_000, &dex), vec![MAX_LEVEL]);
+        assert_eq!(member.xp, 0);
+        assert_eq!(member.xp_to_next_level(), None);
+    }
+
+    fn attack(id: &str) -> AttackId {
+        AttackId(String::from(id))
+    }
+
+    #[test]
+    fn relearning_needs_the_level_and_the_learnset() {
+        let dex = Dex::from_base_files();
+        let mut member = TeamMember::at_level(creature("flammiaou", &dex), 2, &dex);
+        member.moves = vec![attack("lancebraiz")];
+        // learnt at level 3
+        assert!(!member.relearn(&attack("lick"), None, &dex));
+        // cats have no legs to kick with
+        assert!(!member.relearn(&attack("kick"), None, &dex));
+        // known already
+        assert!(!member.relearn(&attack("lancebraiz"), None, &dex));
+        assert_eq!(member.moves, [attack("lancebraiz")]);
+
+        member.level = 3;
+        assert!(member.relearn(&attack("lick"), None, &dex));
+        assert_eq!(member.moves, [attack("lancebraiz"), attack("lick")]);
+    }
+
+    #[test]
+    fn relearning_replaces_the_chosen_slot_when_full() {
+        let dex = Dex::from_base_files();
+        let mut member = TeamMember::at_level(creature("flammiaou", &dex), 8, &dex);
+        member.moves = vec![
+            attack("lancebraiz"),
+            attack("listen"),
+            attack("lick"),
+            attack("clean"),
+        ];
+        assert!(!member.relearn(&attack("lick_wounds"), None, &dex));
+        assert!(member.relearn(&attack("lick_wounds"), Some(1), &dex));
+        assert_eq!(
+            member.moves,
+            [
+                attack("lancebraiz"),
+                attack("lick_wounds"),
+                attack("lick"),
+                attack("clean"),
+            ]
+        );
+    }
 }
@@ -0,0 +1,428 @@
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use bevy_egui::{
+    EguiContexts,
+    egui::{self, Color32, Pos2, Rect},
+};
+
+use crate::{
+    AppState,
+    dex::{Attack, Creature, Dex, Effectiveness},
+    fight::{
+        Battle, BattleAction, BattleEvent, BattleLog, FightState, FleeAttemptEvent, Side,
+        TeamSelection, Trainer,
+    },
+    items::{Inventory, Item, ItemCatalog},
+    taming::TameAttemptEvent,
+    team::{Team, TeamMember},
+};
+
+/// How many lines of the battle log are displayed.
+const LOG_LINES: usize = 4;
+
+/// HP bar of a fighting creature.
+fn hp_bar(member: &TeamMember, dex: &Dex) -> egui::ProgressBar {
+    let max_hp = member.max_hp(dex);
+    egui::ProgressBar::new(member.hp as f32 / max_hp as f32)
+        .desired_height(8.)
+        .desired_width(100.)
+        .fill(if member.hp >= (max_hp as f32 * 0.8).round() as u8 {
+            Color32::GREEN
+        } else if member.hp >= (max_hp as f32 * 0.2).round() as u8 {
+            Color32::ORANGE
+        } else {
+            Color32::RED
+        })
+}
+
+/// Sprite, name and HP of a creature on the field. Player's creatures face right.
+fn combatant_ui(ui: &mut egui::Ui, member: &TeamMember, dex: &Dex, flip: bool, size: f32) {
+    ui.vertical(|ui| {
+        let mut image = egui::Image::new(format!("file://assets/{}", member.texture_path(dex)))
+            .fit_to_exact_size(egui::Vec2::new(size, size));
+        if flip {
+            image = image.uv(Rect::from_min_max(Pos2::new(1., 0.), Pos2::new(0., 1.)));
+        }
+        ui.add(image);
+        ui.label(format!("{} Lv. {}", member.name(dex), member.level));
+        ui.add(hp_bar(member, dex));
+    });
+}
+
+/// Add a hint to the label of a button about how the attack would fare against `target`.
+fn with_hint(label: String, attack: &dyn Attack, target: Option<&TeamMember>, dex: &Dex) -> String {
+    match target.map(|t| Effectiveness::from(dex.multiplier(attack, t))) {
+        Some(Effectiveness::Normal) | None => label,
+        Some(effectiveness) => format!("{label} ({effectiveness})"),
+    }
+}
+
+/// Let the next creature of the player choose its action, or the foes once they all did.
+fn after_choice(battle: &Battle) -> FightState {
+    if battle.next_to_choose(Side::Player).is_some() {
+        FightState::MainAction
+    } else {
+        FightState::EnemyTurn
+    }
+}
+
+/// Build the fight window. It only renders the `Battle` and stores the player's choices,
+/// the fight itself is resolved by the `FightPlugin`.
+#[allow(clippy::too_many_arguments)]
+pub fn setup_fight_ui(
+    mut contexts: EguiContexts,
+    wild_creature: Option<Res<Creature>>,
+    trainer: Option<Res<Trainer>>,
+    battle: Option<ResMut<Battle>>,
+    log: Res<BattleLog>,
+    dex: Res<Dex>,
+    fight_state: Res<State<FightState>>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut next_fight_state: ResMut<NextState<FightState>>,
+    mut attack_choice: Local<Option<Arc<dyn Attack + Send + Sync>>>,
+    mut tame_writer: EventWriter<TameAttemptEvent>,
+    mut flee_writer: EventWriter<FleeAttemptEvent>,
+    mut battle_events: EventWriter<BattleEvent>,
+    inventory: Res<Inventory>,
+    catalog: Res<ItemCatalog>,
+    mut item_choice: Local<Option<Item>>,
+) -> Result {
+    let Some(mut battle) = battle else {
+        return Ok(());
+    };
+    let ctx = contexts.ctx_mut()?;
+    let max_rect = ctx.available_rect();
+
+    // only wild creatures can be tamed
+    let wild = trainer.is_none();
+    let title = if let Some(trainer) = trainer {
+        format!("{} wants to fight!", trainer.name)
+    } else if let Some(creature) = wild_creature {
+        format!("A wild {} wants to fight!", creature.name)
+    } else {
+        String::from("Fight!")
+    };
+    // creature of the player currently choosing its action
+    let fighter = battle.next_to_choose(Side::Player);
+    let foes = battle.actives(Side::Foe);
+    // creatures waiting for their turn, and not already called by another switch
+    let bench: Vec<usize> = battle
+        .bench(Side::Player)
+        .into_iter()
+        .filter(|idx| {
+            !battle.actions.iter().any(
+                |action| matches!(action, BattleAction::Switch { target, .. } if target == idx),
+            )
+        })
+        .collect();
+    // items doing something in fights, left once the ones already chosen this round are used
+    let items: Vec<(&Item, u32)> = catalog
+        .0
+        .iter()
+        .filter(|item| item.usable_in_fight())
+        .filter_map(|item| {
+            let chosen = battle
+                .actions
+                .iter()
+                .filter(|action| {
+                    matches!(action, BattleAction::UseItem { item: chosen, .. } if chosen.name == item.name)
+                })
+                .count() as u32;
+            let left = inventory.count(&item.name).saturating_sub(chosen);
+            (left > 0).then_some((item, left))
+        })
+        .collect();
+    let sprite_size = 128. / battle.slots as f32;
+
+    egui::Window::new(title)
+        .resizable(false)
+        .max_height(max_rect.height() * 0.5)
+        .show(ctx, |ui| {
+            ui.vertical(|ui| {
+                ui.horizontal(|ui| {
+                    for idx in battle.actives(Side::Player) {
+                        combatant_ui(ui, &battle.combatants[idx].member, &dex, true, sprite_size);
+                    }
+                    ui.add_space(max_rect.width() - 256. - 32.);
+                    for idx in foes.iter() {
+                        combatant_ui(
+                            ui,
+                            &battle.combatants[*idx].member,
+                            &dex,
+                            false,
+                            sprite_size,
+                        );
+                    }
+                });
+                let skip = log.0.len().saturating_sub(LOG_LINES);
+                for line in log.0.iter().skip(skip) {
+                    ui.label(line);
+                }
+                match fight_state.get() {
+                    FightState::MainAction => {
+                        if let Some(idx) = fighter {
+                            ui.label(format!(
+                                "What will {} do?",
+                                battle.combatants[idx].member.name(&dex)
+                            ));
+                        }
+                        ui.horizontal_centered(|ui| {
+                            if ui.button("Attack").clicked() {
+                                next_fight_state.set(FightState::AttackChoice);
+                            }
+                            if ui.add_enabled(wild, egui::Button::new("Tame")).clicked() {
+                                tame_writer.write(TameAttemptEvent);
+                            }
+                            if ui
+                                .add_enabled(!bench.is_empty(), egui::Button::new("Switch"))
+                                .clicked()
+                            {
+                                next_fight_state.set(FightState::SwitchChoice);
+                            }
+                        });
+                        ui.horizontal_centered(|ui| {
+                            if ui
+                                .add_enabled(!items.is_empty(), egui::Button::new("Items"))
+                                .clicked()
+                            {
+                                next_fight_state.set(FightState::ItemChoice);
+                            }
+                            // trainers do not let the player run away
+                            if ui.add_enabled(wild, egui::Button::new("Flee")).clicked() {
+                                flee_writer.write(FleeAttemptEvent);
+                            }
+                        });
+                    }
+                    FightState::AttackChoice => {
+                        ui.horizontal_top(|ui| {
+                            // the attacks the creature knows, not all the ones it could learn
+                            if let Some(idx) = fighter {
+                                // hint only when there is no doubt about the target
+                                let foe = match foes.as_slice() {
+                                    [foe] => Some(&battle.combatants[*foe].member),
+                                    _ => None,
+                                };
+                                for attack in dex.moves_of(&battle.combatants[idx].member) {
+                                    if ui
+                                        .button(with_hint(
+                                            attack.name(),
+                                            attack.as_ref(),
+                                            foe,
+                                            &dex,
+                                        ))
+                                        .clicked()
+                                    {
+                                        *attack_choice = Some(Arc::clone(&attack));
+                                        next_fight_state.set(FightState::TargetChoice);
+                                    };
+                                }
+                            }
+                            // a creature knowing no attack can still switch or use an item
+                            if ui.button("Back").clicked() {
+                                next_fight_state.set(FightState::MainAction);
+                            }
+                        });
+                    }
+                    FightState::TargetChoice => {
+                        let (Some(source), Some(attack)) = (fighter, attack_choice.clone()) else {
+                            next_fight_state.set(FightState::MainAction);
+                            return;
+                        };
+                        let candidates = battle.candidates(source, attack.target_type());
+                        // no need to ask when there is no choice
+                        let mut target = match candidates.as_slice() {
+                            [] => Some(source),
+                            [only] => Some(*only),
+                            _ => None,
+                        };
+                        ui.horizontal_top(|ui| {
+                            for idx in candidates.iter() {
+                                let member = &battle.combatants[*idx].member;
+                                let label = with_hint(
+                                    member.name(&dex),
+                                    attack.as_ref(),
+                                    Some(member),
+                                    &dex,
+                                );
+                                if ui.button(label).clicked() {
+                                    target = Some(*idx);
+                                }
+                            }
+                            if ui.button("Back").clicked() {
+                                *attack_choice = None;
+                                next_fight_state.set(FightState::AttackChoice);
+                            }
+                        });
+                        if let Some(target) = target {
+                            battle.choose(BattleAction::Attack {
+                                source,
+                                attack,
+                                target,
+                            });
+                            *attack_choice = None;
+                            next_fight_state.set(after_choice(&battle));
+                        }
+                    }
+                    FightState::ItemChoice => {
+                        let Some(source) = fighter else {
+                            next_fight_state.set(FightState::MainAction);
+                            return;
+                        };
+                        let Some(item) = item_choice.clone() else {
+                            ui.horizontal_top(|ui| {
+                                for (item, left) in items.iter() {
+                                    if ui
+                                        .button(format!("{} x{left}", item.name))
+                                        .on_hover_text(&item.description)
+                                        .clicked()
+                                    {
+                                        *item_choice = Some((*item).clone());
+                                    }
+                                }
+                                if ui.button("Back").clicked() {
+                                    next_fight_state.set(FightState::MainAction);
+                                }
+                            });
+                            return;
+                        };
+                        // items go to the player's creatures on the field
+                        let allies = battle.actives(Side::Player);
+                        let mut target = match allies.as_slice() {
+                            [only] => Some(*only),
+                            _ => None,
+                        };
+                        ui.horizontal_top(|ui| {
+                            for idx in allies.iter() {
+                                if ui
+                                    .button(battle.combatants[*idx].member.name(&dex))
+                                    .clicked()
+                                {
+                                    target = Some(*idx);
+                                }
+                            }
+                            if ui.button("Back").clicked() {
+                                *item_choice = None;
+                            }
+                        });
+                        if let Some(target) = target {
+                            battle.choose(BattleAction::UseItem {
+                                source,
+                                item,
+                                target,
+                            });
+                            *item_choice = None;
+                            next_fight_state.set(after_choice(&battle));
+                        }
+                    }
+                    FightState::SwitchChoice => {
+                        // a creature knocked out must be replaced before anything else
+                        let forced = battle.needs_replacement();
+                        if forced {
+                            ui.label("Who will enter the fight?");
+                        } else if let Some(idx) = fighter {
+                            ui.label(format!(
+                                "Who will take {}'s place?",
+                                battle.combatants[idx].member.name(&dex)
+                            ));
+                        }
+                        let mut chosen = None;
+                        ui.horizontal_top(|ui| {
+                            for idx in bench.iter() {
+                                let member = &battle.combatants[*idx].member;
+                                if ui
+                                    .button(format!("{} Lv. {}", member.name(&dex), member.level))
+                                    .clicked()
+                                {
+                                    chosen = Some(*idx);
+                                }
+                            }
+                            if !forced && ui.button("Back").clicked() {
+                                next_fight_state.set(FightState::MainAction);
+                            }
+                        });
+                        if let Some(target) = chosen {
+                            if forced {
+                                battle_events.write_batch(battle.send_out(target));
+                                if !battle.needs_replacement() {
+                                    next_fight_state.set(FightState::MainAction);
+                                }
+                            } else if let Some(source) = fighter {
+                                // switching takes the creature's turn
+                                battle.choose(BattleAction::Switch { source, target });
+                                next_fight_state.set(after_choice(&battle));
+                            }
+                        }
+                    }
+                    FightState::Action | FightState::EnemyTurn => {
+                        ui.label("...");
+                    }
+                    FightState::Win | FightState::Lose | FightState::Tamed => {
+                        if ui.button("Continue").clicked() {
+                            next_state.set(AppState::InGame);
+                        }
+                    }
+                    // the team is chosen in its own window, before the battle begins
+                    FightState::Start => {}
+                    FightState::SourceChoice => {
+                        unreachable!("creatures choose their action in turn, none is picked")
+                    }
+                };
+            });
+        });
+
+    Ok(())
+}
+
+/// Build the window where the player chooses the creatures that will fight.
+pub fn setup_team_selection_ui(
+    mut contexts: EguiContexts,
+    selection: Option<ResMut<TeamSelection>>,
+    team: Res<Team>,
+    dex: Res<Dex>,
+    mut next_fight_state: ResMut<NextState<FightState>>,
+) -> Result {
+    let Some(mut selection) = selection else {
+        return Ok(());
+    };
+    let ctx = contexts.ctx_mut()?;
+
+    egui::Window::new("Choose your creatures")
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(format!("Choose up to {} creatures.", TeamSelection::MAX));
+            ui.horizontal(|ui| {
+                for (slot, member) in team.0.iter().enumerate() {
+                    // show the order of entry
+                    let position = selection.0.iter().position(|s| *s == slot);
+                    let label = match position {
+                        Some(position) => format!("{}. {}", position + 1, member.name(&dex)),
+                        None => member.name(&dex),
+                    };
+                    ui.add_enabled_ui(!member.is_ko(), |ui| {
+                        ui.vertical(|ui| {
+                            ui.add(
+                                egui::Image::new(format!(
+                                    "file://assets/{}",
+                                    member.texture_path(&dex)
+                                ))
+                                .fit_to_exact_size(egui::Vec2::new(64., 64.)),
+                            );
+                            if ui.selectable_label(position.is_some(), label).clicked() {
+                                selection.toggle(slot, &team);
+                            }
+                            ui.add(hp_bar(member, &dex));
+                        });
+                    });
+                }
+            });
+            if ui
+                .add_enabled(!selection.0.is_empty(), egui::Button::new("Fight!"))
+                .clicked()
+            {
+                next_fight_state.set(FightState::MainAction);
+            }
+        });
+
+    Ok(())
+}
@@ -3,35 +3,41 @@ use std::sync::Arc;
 use bevy::prelude::*;
 use bevy_egui::{
     EguiContexts,
-    egui::{self, Color32, Frame, Pos2, Rect, RichText},
+    egui::{self, Color32, Frame, Pos2, RichText},
 };
 
 use crate::{
     AppState,
     camera::WorldTexture,
-    dex::{Attack, Creature, Dex},
-    event::NewSaveEvent,
-    fight::FightState,
-    team::Team,
+    dex::{Attack, Dex},
+    event::{NewSaveEvent, RelearnMoveEvent},
+    evolution::{EvolveEvent, PendingEvolutions},
+    items::{Inventory, ItemCatalog, UseItemEvent},
+    taming::Rings,
+    team::{MAX_MOVES, Team},
     ui::{index::dex_list_ui, widgets::MenuButton},
 };
 
 /// Build the whole game UI
 /// What it shows depends on the current `AppState`
+#[allow(clippy::too_many_arguments)]
 pub fn setup_game_ui(
     mut contexts: EguiContexts,
     mut event_writer: EventWriter<NewSaveEvent>,
-    wild_creature: Option<ResMut<Creature>>,
     state: Res<State<AppState>>,
     team: Res<Team>,
+    rings: Res<Rings>,
     world_tex: Res<WorldTexture>,
-    mut next_state: ResMut<NextState<AppState>>,
-    mut next_fight_state: ResMut<NextState<FightState>>,
-    fight_state: Res<State<FightState>>,
     dex: Res<Dex>,
+    inventory: Res<Inventory>,
+    catalog: Res<ItemCatalog>,
+    mut item_writer: EventWriter<UseItemEvent>,
+    mut relearn_writer: EventWriter<RelearnMoveEvent>,
     mut enable_index: Local<bool>,
-    // let mut attack_choice: Option<Arc<dyn Attack + Send + Sync>> = None;
-    mut attack_choice: Local<Option<Arc<dyn Attack + Send + Sync>>>,
+    mut enable_items: Local<bool>,
+    mut item_choice: Local<Option<String>>,
+    mut moves_member: Local<Option<usize>>,
+    mut learn_choice: Local<Option<Arc<dyn Attack + Send + Sync>>>,
 ) -> Result {
     // textures
     let world_texture_id = contexts.image_id(&world_tex).unwrap();
@@ -49,7 +55,7 @@ pub fn setup_game_ui(
         .min_width(200.0)
         .show(ctx, |ui| {
             ui.with_layout(egui::Layout::top_down(egui::Align::RIGHT), |ui| {
-                for member in team.0.iter() {
+                for (member_idx, member) in team.0.iter().enumerate() {
                     Frame::new()
                         .stroke(egui::Stroke::new(1., Color32::GRAY))
                         .corner_radius(2)
@@ -76,7 +82,12 @@ pub fn setup_game_ui(
                                 );
                                 ui.vertical(|ui: &mut egui::Ui| {
                                     ui.label(
-                                        RichText::new(member.name(&dex)).color(Color32::WHITE),
+                                        RichText::new(format!(
+                                            "{} Lv. {}",
+                                            member.name(&dex),
+                                            member.level
+                                        ))
+                                        .color(Color32::WHITE),
                                     );
                                     let hp_bar = egui::ProgressBar::new(
                                         member.hp as f32 / member.max_hp(&dex) as f32,
@@ -103,11 +114,25 @@ pub fn setup_game_ui(
                                             member.max_hp(&dex)
                                         ))
                                         .color(Color32::WHITE),
-                                    )
+                                    );
+                                    if ui
+                                        .add_enabled(
+                                            *state == AppState::InGame,
+                                            egui::Button::new("Moves").small(),
+                                        )
+                                        .clicked()
+                                    {
+                                        *moves_member = Some(member_idx);
+                                        *learn_choice = None;
+                                    }
                                 })
                             });
                         });
                 }
+                // rings waiting for a creature
+                for ring in rings.0.iter() {
+                    ui.label(format!("Empty {ring}"));
+                }
             });
         });
     egui::TopBottomPanel::top("actions_panel")
@@ -116,6 +141,7 @@ pub fn setup_game_ui(
             ui.horizontal_centered(|ui| {
                 if *state == AppState::InGame {
                     ui.checkbox(&mut enable_index, "Index");
+                    ui.checkbox(&mut enable_items, "Items");
                     // save = ui.button("Save").clicked();
                     save = ui.add(MenuButton::new("Save")).clicked();
                 } else {
@@ -146,127 +172,6 @@ pub fn setup_game_ui(
                 rect,
                 egui::Image::new("file://assets/textures/animations/blue_prism.gif"),
             );
-
-            // Fight floating window!
-            let (title, mut foes) = if let Some(creature) = wild_creature {
-                (
-                    format!("A wild {} wants to fight!", creature.name),
-                    vec![creature],
-                )
-            } else {
-                todo!("need to implment fight versus trainer");
-                (format!("A trainer wants to fight!"), vec![])
-            };
-
-            // Actions variable
-            // let mut attack_choice: Option<Arc<dyn Attack + Send + Sync>> = None;
-
-            egui::Window::new(title)
-                .resizable(false)
-                .max_height(max_rect.height() * 0.5)
-                .show(ctx, |ui| {
-                    ui.vertical(|ui| {
-                        ui.horizontal(|ui| {
-                            ui.vertical(|ui| {
-                                ui.add(
-                                    egui::Image::new(format!(
-                                        "file://assets/{}",
-                                        team.0[0].texture_path(&dex)
-                                    ))
-                                    .uv(Rect::from_min_max(Pos2::new(1., 0.), Pos2::new(0., 1.)))
-                                    .fit_to_exact_size(egui::Vec2::new(128., 128.)),
-                                );
-                                let hp_bar = egui::ProgressBar::new(
-                                    team.0[0].hp as f32 / team.0[0].max_hp(&dex) as f32,
-                                )
-                                // todo rendre ca dynamique?
-                                .desired_height(8.)
-                                .desired_width(100.)
-                                .fill(
-                                    if team.0[0].hp
-                                        >= (team.0[0].max_hp(&dex) as f32 * 0.8).round() as u8
-                                    {
-                                        Color32::GREEN
-                                    } else if team.0[0].hp
-                                        >= (team.0[0].max_hp(&dex) as f32 * 0.2).round() as u8
-                                    {
-                                        Color32::ORANGE
-                                    } else {
-                                        Color32::RED
-                                    },
-                                );
-                                ui.add(hp_bar);
-                            });
-                            ui.add_space(max_rect.width() - 256. - 32.);
-                            ui.add(
-                                egui::Image::new(format!("file://{}", foes[0].texture_path()))
-                                    .fit_to_exact_size(egui::Vec2::new(128., 128.)),
-                            );
-                        });
-                        match fight_state.get() {
-                            FightState::MainAction => {
-                                ui.horizontal_centered(|ui| {
-                                    if ui.button("Attack").clicked() {
-                                        next_fight_state.set(FightState::AttackChoice);
-                                    }
-                                    if ui.button("Tame").clicked() {
-                                        // try to tame
-                                    }
-                                });
-                                ui.horizontal_centered(|ui| {
-                                    if ui.button("Items").clicked() {}
-                                    if ui.button("Flee").clicked() {
-                                        next_state.set(AppState::InGame);
-                                    }
-                                });
-                            }
-                            FightState::AttackChoice => {
-                                ui.horizontal_top(|ui| {
-                                    // one elemental attack, plus all attacks defined by physical caracteristics.
-                                    let fighter = team.0.first().unwrap();
-                                    for attack in
-                                        dex.filter_attacks_for_team_member(fighter.clone())
-                                    {
-                                        if ui.button(attack.name()).clicked() {
-                                            *attack_choice = Some(Arc::clone(&attack));
-                                            next_fight_state.set(FightState::TargetChoice);
-                                        };
-                                    }
-                                });
-                            }
-                            FightState::TargetChoice => {
-                                // todo: faire pour de vraiplutot que ce hack
-                                if foes.len() == 1 {
-                                    next_fight_state.set(FightState::Action);
-                                } else {
-                                    todo!("pas encore implemente les cbt dresseurs");
-                                }
-                            }
-                            FightState::Action => {
-                                if let Some(attack) = attack_choice.clone() {
-                                    println!("on attack avec {}", attack.name());
-                                    println!(
-                                        "ca fait {} degats bruts",
-                                        attack.damage().unwrap_or(0)
-                                    );
-                                    // TODO ICI
-                                    // Ok la ca ne va pas, ca doit etre des teammebers aussi en face
-                                    let foe = foes.get_mut(0).unwrap();
-                                    // reset action
-                                    *attack_choice = None;
-                                    next_fight_state.set(FightState::EnemyTurn);
-                                } else {
-                                    todo!("action other than attack not implemented !");
-                                }
-                            }
-                            _ => {
-                                // while not implemented, fall back to main
-                                println!("{fight_state:?} not implemented !");
-                                next_fight_state.set(FightState::MainAction)
-                            }
-                        };
-                    });
-                });
         }
     });
 
@@ -276,6 +181,95 @@ pub fn setup_game_ui(
         .show(ctx, |ui| {
             dex_list_ui(ui, &dex);
         });
+    egui::Window::new("Items")
+        .open(&mut enable_items)
+        .show(ctx, |ui| {
+            if let Some(name) = item_choice.clone() {
+                ui.label(format!("Use a {name} on..."));
+                for (member_idx, member) in team.0.iter().enumerate() {
+                    if ui
+                        .add_enabled(!member.is_ko(), egui::Button::new(member.name(&dex)))
+                        .clicked()
+                    {
+                        item_writer.write(UseItemEvent {
+                            item: name.clone(),
+                            member: member_idx,
+                        });
+                        *item_choice = None;
+                    }
+                }
+                if ui.button("Back").clicked() {
+                    *item_choice = None;
+                }
+                return;
+            }
+            for (name, count) in inventory.0.iter() {
+                let Some(item) = catalog.get(name) else {
+                    continue;
+                };
+                // effects only last for a fight
+                if ui
+                    .add_enabled(
+                        item.usable_outside_fight(),
+                        egui::Button::new(format!("{name} x{count}")),
+                    )
+                    .on_hover_text(&item.description)
+                    .clicked()
+                {
+                    *item_choice = Some(name.clone());
+                }
+            }
+        });
+    // attacks known by a member, and the ones it can learn again
+    let mut moves_open = moves_member.is_some() && *state == AppState::InGame;
+    if let Some(member_idx) = *moves_member
+        && let Some(member) = team.0.get(member_idx)
+    {
+        egui::Window::new(format!("Moves of {}", member.name(&dex)))
+            .open(&mut moves_open)
+            .show(ctx, |ui| {
+                if let Some(attack) = learn_choice.as_ref() {
+                    ui.label(format!("Forget which attack to learn {}?", attack.name()));
+                } else {
+                    ui.label("Knows:");
+                }
+                for (slot, known) in member.moves.iter().enumerate() {
+                    let name = dex
+                        .get_attack(known)
+                        .map_or(known.to_string(), |atk| atk.name());
+                    if ui.button(name).clicked()
+                        && let Some(attack) = learn_choice.take()
+                    {
+                        relearn_writer.write(RelearnMoveEvent {
+                            member: member_idx,
+                            attack: attack.id(),
+                            forget: Some(slot),
+                        });
+                    }
+                }
+                ui.separator();
+                ui.label("Can learn:");
+                for attack in dex.filter_attacks_for_team_member(member.clone()) {
+                    if member.moves.contains(&attack.id()) {
+                        continue;
+                    }
+                    if ui.button(attack.name()).clicked() {
+                        if member.moves.len() < MAX_MOVES {
+                            relearn_writer.write(RelearnMoveEvent {
+                                member: member_idx,
+                                attack: attack.id(),
+                                forget: None,
+                            });
+                        } else {
+                            *learn_choice = Some(attack);
+                        }
+                    }
+                }
+            });
+    }
+    if !moves_open {
+        *moves_member = None;
+    }
     if save {
         event_writer.write(NewSaveEvent {});
     }
@@ -284,6 +278,48 @@ pub fn setup_game_ui(
     Ok(())
 }
 
+/// Ask the player whether the first pending evolution happens.
+pub fn setup_evolution_ui(
+    mut contexts: EguiContexts,
+    pending: Res<PendingEvolutions>,
+    team: Res<Team>,
+    dex: Res<Dex>,
+    mut evolve_writer: EventWriter<EvolveEvent>,
+) -> Result {
+    let Some(evolution) = pending.0.front() else {
+        return Ok(());
+    };
+    let (Some(member), Some((into, _))) = (
+        team.0.get(evolution.member),
+        dex.get_creature(&evolution.into),
+    ) else {
+        return Ok(());
+    };
+    let ctx = contexts.ctx_mut()?;
+    egui::Window::new("Evolution")
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(format!(
+                "{} is evolving into {}!",
+                member.name(&dex),
+                into.name
+            ));
+            if let Some(item) = evolution.item.as_ref() {
+                ui.label(format!("The {item} will be used up."));
+            }
+            ui.horizontal(|ui| {
+                if ui.button("Evolve").clicked() {
+                    evolve_writer.write(EvolveEvent { confirm: true });
+                }
+                if ui.button("Cancel").clicked() {
+                    evolve_writer.write(EvolveEvent { confirm: false });
+                }
+            });
+        });
+    Ok(())
+}
+
 /// Game UI specific input handling
 /// Does not cover in-game actions like moving the player
 pub fn handle_game_ui_input(
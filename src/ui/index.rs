@@ -1,6 +1,6 @@
 use bevy_egui::egui::{self, Grid, RichText};
 
-use crate::dex::{Creature, Dex, Species};
+use crate::dex::{Creature, Dex, Pack, PackState, Species, size_dodge};
 
 /// Basic brick for the Dex. It shows basic information about a creature.
 // todo: hover/click for more data.
@@ -29,7 +29,13 @@ fn creature_entry_ui(ui: &mut egui::Ui, creature: &Creature) -> egui::Response {
 
 fn species_dropdown_ui(ui: &mut egui::Ui, species: &Species) -> egui::Response {
     ui.vertical(|ui| {
-        egui::CollapsingHeader::new(&species.name).show(ui, |ui| {
+        egui::CollapsingHeader::new(format!("{} ({})", species.name, species.pack)).show(ui, |ui| {
+            ui.label(format!(
+                "{} kg, {} m, +{}% dodge from its size",
+                species.mass_kg,
+                species.height_m,
+                size_dodge(species.height_m)
+            ));
             for creature in &species.individuals {
                 creature_entry_ui(ui, &creature.0);
                 ui.separator();
@@ -39,11 +45,37 @@ fn species_dropdown_ui(ui: &mut egui::Ui, species: &Species) -> egui::Response {
     .response
 }
 
+/// Where a pack comes from and whether it made it into the dex, with its errors if not.
+fn pack_entry_ui(ui: &mut egui::Ui, pack: &Pack) {
+    let state = match &pack.state {
+        PackState::Enabled => String::from("enabled"),
+        PackState::Disabled => String::from("disabled"),
+        PackState::Rejected(errors) => format!("left out, {} errors", errors.len()),
+    };
+    let header = format!("{} ({}): {state}", pack.name, pack.file);
+    if let PackState::Rejected(errors) = &pack.state {
+        egui::CollapsingHeader::new(header).show(ui, |ui| {
+            for error in errors {
+                ui.label(error);
+            }
+        });
+    } else {
+        ui.label(header);
+    }
+}
+
 // Todo: filters
 pub fn dex_list_ui(ui: &mut egui::Ui, dex: &Dex) {
     egui::ScrollArea::vertical().show(ui, |ui| {
         for species in &dex.species {
             species_dropdown_ui(ui, species);
         }
+        if !dex.packs.is_empty() {
+            ui.separator();
+            ui.label(RichText::new("Packs").heading());
+            for pack in &dex.packs {
+                pack_entry_ui(ui, pack);
+            }
+        }
     });
 }
@@ -4,12 +4,14 @@ use bevy_egui::{
     egui::{self, Color32},
 };
 
-use crate::{AppState, save::Save};
+use crate::{AppState, dex::Dex, save::Save};
 
 /// Build the "main menu" window, with a few buttons: Continue (if save exists), New Game, and Options.
+/// Playing is only possible once the dex data is loaded.
 pub fn setup_main_menu_ui(
     mut contexts: EguiContexts,
     mut next_state: ResMut<NextState<AppState>>,
+    dex: Option<Res<Dex>>,
 ) -> Result {
     // buttons states
     let mut resume = false;
@@ -37,15 +39,28 @@ pub fn setup_main_menu_ui(
 
         ui.separator();
 
-        let mut buttons = vec![("New Game", &mut new), ("Options", &mut options)];
+        let loaded = dex.is_some();
+        let mut buttons = vec![
+            ("New Game", loaded, &mut new),
+            ("Options", true, &mut options),
+        ];
         if Save::exists() {
-            buttons.insert(0, ("Resume", &mut resume));
+            buttons.insert(0, ("Resume", loaded, &mut resume));
         }
 
         ui.vertical_centered(|ui| {
-            for (str, state) in buttons {
+            for (str, enabled, state) in buttons {
                 ui.add_space(12.);
-                *state = ui.button(egui::RichText::new(str).size(24.)).clicked();
+                *state = ui
+                    .add_enabled(
+                        enabled,
+                        egui::Button::new(egui::RichText::new(str).size(24.)),
+                    )
+                    .clicked();
+            }
+            if !loaded {
+                ui.add_space(12.);
+                ui.label("Loading...");
             }
         });
     });
@@ -55,7 +70,7 @@ pub fn setup_main_menu_ui(
     }
 
     if new {
-        next_state.set(AppState::InGame);
+        next_state.set(AppState::NewGame);
     }
 
     if options {
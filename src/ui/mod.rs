@@ -1,18 +1,22 @@
 use bevy::prelude::*;
 
+mod fight;
 mod game;
 mod index;
 mod main_menu;
+mod new_game;
 mod widgets;
 
 use bevy_egui::{
     EguiContexts, EguiPlugin, EguiPrimaryContextPass,
     egui::{FontData, FontDefinitions, FontFamily},
 };
+use fight::*;
 use game::*;
 use main_menu::*;
+use new_game::*;
 
-use crate::AppState;
+use crate::{AppState, fight::FightState};
 
 pub struct UiPlugin;
 
@@ -24,8 +28,18 @@ impl Plugin for UiPlugin {
             EguiPrimaryContextPass,
             (
                 setup_main_menu_ui.run_if(in_state(AppState::MainMenu)),
+                setup_new_game_ui.run_if(in_state(AppState::NewGame)),
                 handle_game_ui_input.run_if(in_state(AppState::InGame)),
                 setup_game_ui.run_if(in_state(AppState::InGame).or(in_state(AppState::InFight))),
+                setup_fight_ui
+                    .after(setup_game_ui)
+                    .run_if(in_state(AppState::InFight)),
+                setup_team_selection_ui
+                    .after(setup_game_ui)
+                    .run_if(in_state(AppState::InFight).and(in_state(FightState::Start))),
+                setup_evolution_ui
+                    .after(setup_game_ui)
+                    .run_if(in_state(AppState::InGame)),
             ),
         );
         app.add_systems(
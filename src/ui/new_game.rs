@@ -0,0 +1,60 @@
+use bevy::prelude::*;
+use bevy_egui::{
+    EguiContexts,
+    egui::{self, Color32},
+};
+
+use crate::{
+    AppState,
+    taming::{PlayerAura, Questionnaire, QuestionnaireAnswers},
+};
+
+/// Ask the questions of the `Questionnaire` one by one, then tell the player their aura.
+pub fn setup_new_game_ui(
+    mut contexts: EguiContexts,
+    questionnaire: Res<Questionnaire>,
+    mut answers: ResMut<QuestionnaireAnswers>,
+    mut player_aura: ResMut<PlayerAura>,
+    mut next_state: ResMut<NextState<AppState>>,
+) -> Result {
+    let ctx = contexts.ctx_mut()?;
+
+    egui::CentralPanel::default().show(ctx, |ui| {
+        ui.vertical_centered(|ui| {
+            ui.add_space(24.);
+            match questionnaire.questions.get(answers.0.len()) {
+                Some(question) => {
+                    ui.heading(
+                        egui::RichText::new(&question.text)
+                            .color(Color32::WHITE)
+                            .size(24.),
+                    );
+                    for (idx, answer) in question.answers.iter().enumerate() {
+                        ui.add_space(12.);
+                        if ui
+                            .button(egui::RichText::new(&answer.text).size(18.))
+                            .clicked()
+                        {
+                            answers.0.push(idx);
+                        }
+                    }
+                }
+                None => {
+                    let aura = questionnaire.aura(&answers.0);
+                    ui.heading(
+                        egui::RichText::new(format!("Your aura is {aura}."))
+                            .color(Color32::WHITE)
+                            .size(24.),
+                    );
+                    ui.add_space(12.);
+                    if ui.button(egui::RichText::new("Start").size(24.)).clicked() {
+                        player_aura.0 = aura;
+                        next_state.set(AppState::InGame);
+                    }
+                }
+            }
+        });
+    });
+
+    Ok(())
+}
@@ -2,9 +2,20 @@
 //!
 //! just short functions to read ldtks entities custom data
 
-use bevy_ecs_ldtk::{EntityInstance, ldtk::FieldValue};
+use std::ops::RangeInclusive;
 
-use crate::{utils::Direction, world::NPCKind};
+use bevy_ecs_ldtk::{
+    EntityInstance,
+    ldtk::{FieldInstance, FieldValue},
+};
+
+use crate::{
+    dex::{Creature, Dex},
+    fight::AiKind,
+    team::MAX_LEVEL,
+    utils::Direction,
+    world::NPCKind,
+};
 
 /// Extract string
 pub fn read_str_from_ldtk_entity(key: &str, entity: &EntityInstance) -> String {
@@ -46,3 +57,115 @@ pub fn read_dir_from_ldtk_entity(entity: &EntityInstance) -> Direction {
         x => panic!(" {x:?} is not a direction ?"),
     }
 }
+
+/// Creatures of a trainer, by name, each with its level when written after it, such as
+/// `Flammiaou 5`. Empty if the NPC does not fight. Creatures missing from the dex are left
+/// out.
+pub fn read_team_from_ldtk_entity<'a>(
+    entity: &EntityInstance,
+    dex: &'a Dex,
+) -> Vec<(&'a Creature, Option<u8>)> {
+    match entity
+        .field_instances
+        .iter()
+        .find(|f| f.identifier == "team")
+    {
+        Some(field) => match &field.value {
+            FieldValue::Strings(entries) => entries
+                .iter()
+                .flatten()
+                .map(|entry| {
+                    let entry = entry.trim();
+                    entry
+                        .rsplit_once(' ')
+                        .and_then(|(name, level)| Some((name.trim(), level.parse::<u8>().ok()?)))
+                        .map(|(name, level)| (name.to_string(), Some(level.clamp(1, MAX_LEVEL))))
+                        .unwrap_or((entry.to_string(), None))
+                })
+                .filter_map(|(name, level)| match dex.find_creature_by_name(&name) {
+                    Some(creature) => Some((creature, level)),
+                    None => {
+                        eprintln!("unknown creature `{name}` in a team, it is left out");
+                        None
+                    }
+                })
+                .collect(),
+            x => panic!(" {x:?} is not a team ?"),
+        },
+        None => vec![],
+    }
+}
+
+/// AI of the foes, from the fields of an entity or a level. Random if not set, or unknown.
+pub fn read_ai_from_ldtk_fields(key: &str, fields: &[FieldInstance]) -> AiKind {
+    match fields
+        .iter()
+        .find(|f| f.identifier == key)
+        .map(|f| &f.value)
+    {
+        Some(FieldValue::Enum(Some(s))) => s.parse().unwrap_or_else(|error| {
+            eprintln!("{error} in `{key}`, the foes fight at random");
+            AiKind::default()
+        }),
+        Some(FieldValue::Enum(None)) | None => AiKind::default(),
+        Some(x) => {
+            eprintln!(" {x:?} is not an ai, the foes fight at random");
+            AiKind::default()
+        }
+    }
+}
+
+/// Levels of the wild creatures, from the fields of a level. The lowest one is 1 if not set,
+/// the highest one is the lowest if not set.
+pub fn read_levels_from_ldtk_fields(fields: &[FieldInstance]) -> RangeInclusive<u8> {
+    let read = |key: &str| match fields
+        .iter()
+        .find(|f| f.identifier == key)
+        .map(|f| &f.value)
+    {
+        Some(FieldValue::Int(Some(level))) => Some((*level).clamp(1, MAX_LEVEL as i32) as u8),
+        Some(FieldValue::Int(None)) | None => None,
+        Some(x) => {
+            eprintln!(" {x:?} is not a level, `{key}` is left out");
+            None
+        }
+    };
+    let min = read("wild_min_level").unwrap_or(1);
+    let max = read("wild_max_level").unwrap_or(min).max(min);
+    min..=max
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn npc_with_team(team: &[&str]) -> EntityInstance {
+        EntityInstance {
+            field_instances: vec![FieldInstance {
+                identifier: "team".to_string(),
+                tile: None,
+                field_instance_type: "Array<String>".to_string(),
+                value: FieldValue::Strings(
+                    team.iter().map(|entry| Some(entry.to_string())).collect(),
+                ),
+                def_uid: 0,
+                real_editor_values: vec![],
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn unknown_creatures_are_left_out_of_teams() {
+        let dex = Dex::from_base_files();
+        let team = read_team_from_ldtk_entity(
+            &npc_with_team(&["Flammiaou 5", "Flamiaou 7", "mulovol"]),
+            &dex,
+        );
+        let team: Vec<(&str, Option<u8>)> = team
+            .iter()
+            .map(|(creature, level)| (creature.name.as_str(), *level))
+            .collect();
+        assert_eq!(team, [("Flammiaou", Some(5)), ("Mulovol", None)]);
+    }
+}
@@ -0,0 +1,26 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::{GridCoords, LdtkEntity};
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Component)]
+pub struct Bed;
+
+#[derive(Default, Bundle, LdtkEntity)]
+pub struct BedBundle {
+    bed: Bed,
+    #[grid_coords]
+    grid_coords: GridCoords,
+}
+
+/// Where the player wakes up after losing a fight: the last bed they used,
+/// or where they started the game.
+#[derive(Resource, Serialize, Deserialize, Clone)]
+pub struct RestPoint {
+    /// Iid of the LDtk level.
+    pub level: String,
+    pub coords: (i32, i32),
+}
+
+/// The player is on their way to the rest point, waiting for its level to be spawned.
+#[derive(Resource)]
+pub struct Arrival(pub RestPoint);
@@ -0,0 +1,37 @@
+mod components;
+mod systems;
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::app::LdtkEntityAppExt;
+pub use components::RestPoint;
+
+use crate::{
+    AppState,
+    fight::FightState,
+    world::beds::{components::BedBundle, systems::*},
+};
+
+pub struct BedsPlugin;
+
+impl Plugin for BedsPlugin {
+    fn build(&self, app: &mut bevy::app::App) {
+        app.register_ldtk_entity::<BedBundle>("Bed")
+            .add_systems(
+                Update,
+                (
+                    add_sprite_to_bed,
+                    handle_player_interaction_with_bed,
+                    record_starting_point,
+                    arrive_at_rest_point,
+                )
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(
+                OnTransition {
+                    exited: AppState::InFight,
+                    entered: AppState::InGame,
+                },
+                respawn_at_rest_point.run_if(in_state(FightState::Lose)),
+            );
+    }
+}
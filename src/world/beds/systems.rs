@@ -0,0 +1,234 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::{
+    GridCoords, LdtkProjectHandle, LevelEvent, LevelIid, LevelSelection,
+    assets::{LdtkProject, LevelMetadataAccessor},
+};
+
+use crate::{
+    dex::Dex,
+    player::Player,
+    team::Team,
+    utils::Direction,
+    world::beds::components::{Arrival, Bed, RestPoint},
+};
+
+/// Same color as in LDtk.
+const BED_COLOR: Color = Color::srgb(0.55, 0.61, 0.71);
+
+/// Iid of the level currently selected. A level selected by its index needs the project
+/// to be loaded.
+fn current_level_iid(
+    level_selection: &LevelSelection,
+    ldtk_project_entities: &Query<&LdtkProjectHandle>,
+    ldtk_project_assets: &Assets<LdtkProject>,
+) -> Option<String> {
+    if let LevelSelection::Iid(iid) = level_selection {
+        return Some(iid.get().clone());
+    }
+    let project = ldtk_project_assets.get(ldtk_project_entities.single().ok()?)?;
+    project
+        .find_raw_level_by_level_selection(level_selection)
+        .map(|level| level.iid.clone())
+}
+
+/// Resting in a bed heals the team, and the player will wake up there after losing a fight.
+#[allow(clippy::too_many_arguments)]
+pub fn handle_player_interaction_with_bed(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    player_q: Query<(&GridCoords, &Direction), With<Player>>,
+    bed_q: Query<&GridCoords, With<Bed>>,
+    mut team: ResMut<Team>,
+    dex: Res<Dex>,
+    level_selection: Res<LevelSelection>,
+    ldtk_project_entities: Query<&LdtkProjectHandle>,
+    ldtk_project_assets: Res<Assets<LdtkProject>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Enter) {
+        let (player_grid_coords, direction) = player_q.single().unwrap();
+        let facing_coords = direction.next_coords(*player_grid_coords);
+        if let Some(bed_coords) = bed_q.iter().find(|coords| **coords == facing_coords)
+            && let Some(level) = current_level_iid(
+                &level_selection,
+                &ldtk_project_entities,
+                &ldtk_project_assets,
+            )
+        {
+            team.heal(&dex);
+            commands.insert_resource(RestPoint {
+                level,
+                coords: (bed_coords.x, bed_coords.y),
+            });
+            info!("Your team is rested.");
+        }
+    }
+}
+
+/// Until a bed is used, the player wakes up where the game started.
+pub fn record_starting_point(
+    mut commands: Commands,
+    player_q: Query<&GridCoords, Added<Player>>,
+    rest_point: Option<Res<RestPoint>>,
+    level_selection: Res<LevelSelection>,
+    ldtk_project_entities: Query<&LdtkProjectHandle>,
+    ldtk_project_assets: Res<Assets<LdtkProject>>,
+) {
+    if rest_point.is_some() {
+        return;
+    }
+    if let Ok(coords) = player_q.single()
+        && let Some(level) = current_level_iid(
+            &level_selection,
+            &ldtk_project_entities,
+            &ldtk_project_assets,
+        )
+    {
+        commands.insert_resource(RestPoint {
+            level,
+            coords: (coords.x, coords.y),
+        });
+    }
+}
+
+/// After a lost fight, heal the team and send the player back to the rest point.
+/// When it is in another level, the player is moved once that level is spawned.
+#[allow(clippy::too_many_arguments)]
+pub fn respawn_at_rest_point(
+    mut commands: Commands,
+    mut player_q: Query<&mut GridCoords, With<Player>>,
+    rest_point: Option<Res<RestPoint>>,
+    mut team: ResMut<Team>,
+    dex: Res<Dex>,
+    level_selection: Res<LevelSelection>,
+    ldtk_project_entities: Query<&LdtkProjectHandle>,
+    ldtk_project_assets: Res<Assets<LdtkProject>>,
+) {
+    team.heal(&dex);
+    let Some(rest_point) = rest_point else {
+        return;
+    };
+    let current = current_level_iid(
+        &level_selection,
+        &ldtk_project_entities,
+        &ldtk_project_assets,
+    );
+    if current.as_ref() == Some(&rest_point.level) {
+        if let Ok(mut player_coords) = player_q.single_mut() {
+            *player_coords = GridCoords::new(rest_point.coords.0, rest_point.coords.1);
+        }
+    } else {
+        commands.insert_resource(LevelSelection::Iid(LevelIid::new(rest_point.level.clone())));
+        commands.insert_resource(Arrival(rest_point.clone()));
+    }
+}
+
+/// Put the player on the rest point once its level is there.
+pub fn arrive_at_rest_point(
+    mut commands: Commands,
+    mut level_events: EventReader<LevelEvent>,
+    mut player_q: Query<&mut GridCoords, With<Player>>,
+    arrival: Option<Res<Arrival>>,
+) {
+    let Some(arrival) = arrival else {
+        return;
+    };
+    for level_event in level_events.read() {
+        if let LevelEvent::Transformed(level_iid) = level_event
+            && *level_iid.get() == arrival.0.level
+            && let Ok(mut player_coords) = player_q.single_mut()
+        {
+            *player_coords = GridCoords::new(arrival.0.coords.0, arrival.0.coords.1);
+            commands.remove_resource::<Arrival>();
+        }
+    }
+}
+
+/// Beds have no texture yet, draw them as a colored tile.
+pub fn add_sprite_to_bed(mut commands: Commands, bed_q: Query<Entity, Added<Bed>>) {
+    for entity in bed_q {
+        commands
+            .entity(entity)
+            .insert(Sprite::from_color(BED_COLOR, Vec2::splat(16.)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::{dex::CreatureId, team::TeamMember};
+
+    /// A world on the level `start`, with a knocked out team and the player at `coords`.
+    fn world(coords: GridCoords) -> World {
+        let dex = Dex::from_base_files();
+        let creature = &dex
+            .get_creature(&CreatureId(String::from("flammiaou")))
+            .unwrap()
+            .0;
+        let mut member = TeamMember::new(creature, &dex);
+        member.hp = 0;
+        let mut world = World::new();
+        world.insert_resource(Team(vec![member]));
+        world.insert_resource(dex);
+        world.insert_resource(LevelSelection::Iid(LevelIid::new("start")));
+        world.init_resource::<Assets<LdtkProject>>();
+        world.init_resource::<Events<LevelEvent>>();
+        world.spawn((Player, coords));
+        world
+    }
+
+    fn player_coords(world: &mut World) -> GridCoords {
+        *world
+            .query_filtered::<&GridCoords, With<Player>>()
+            .single(world)
+            .unwrap()
+    }
+
+    #[test]
+    fn players_without_rest_point_wake_up_where_they_started() {
+        let mut world = world(GridCoords::new(4, 5));
+        world.run_system_once(record_starting_point).unwrap();
+        let rest_point = world.resource::<RestPoint>();
+        assert_eq!(rest_point.level, "start");
+        assert_eq!(rest_point.coords, (4, 5));
+
+        *world
+            .query_filtered::<&mut GridCoords, With<Player>>()
+            .single_mut(&mut world)
+            .unwrap() = GridCoords::new(9, 9);
+        world.run_system_once(respawn_at_rest_point).unwrap();
+        assert_eq!(player_coords(&mut world), GridCoords::new(4, 5));
+        assert!(world.resource::<Team>().can_fight());
+        assert!(world.get_resource::<Arrival>().is_none());
+    }
+
+    #[test]
+    fn players_wake_up_at_their_last_rest_point_in_another_level() {
+        let mut world = world(GridCoords::new(4, 5));
+        world.insert_resource(RestPoint {
+            level: String::from("bedroom"),
+            coords: (2, 3),
+        });
+        // the rest point of the save is kept
+        world.run_system_once(record_starting_point).unwrap();
+        assert_eq!(world.resource::<RestPoint>().level, "bedroom");
+
+        world.run_system_once(respawn_at_rest_point).unwrap();
+        assert!(world.resource::<Team>().can_fight());
+        assert_eq!(
+            *world.resource::<LevelSelection>(),
+            LevelSelection::Iid(LevelIid::new("bedroom"))
+        );
+        assert_eq!(player_coords(&mut world), GridCoords::new(4, 5));
+
+        world.send_event(LevelEvent::Transformed(LevelIid::new("start")));
+        world.run_system_once(arrive_at_rest_point).unwrap();
+        assert_eq!(player_coords(&mut world), GridCoords::new(4, 5));
+
+        world.send_event(LevelEvent::Transformed(LevelIid::new("bedroom")));
+        world.run_system_once(arrive_at_rest_point).unwrap();
+        assert_eq!(player_coords(&mut world), GridCoords::new(2, 3));
+        assert!(world.get_resource::<Arrival>().is_none());
+    }
+}
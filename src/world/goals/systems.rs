@@ -18,6 +18,7 @@ pub fn cache_goal_locations(
     }
 }
 
+#[allow(clippy::type_complexity)]
 pub fn handle_through_goal(
     mut commands: Commands,
     level_goals: ResMut<LevelGoals>,
@@ -25,7 +26,7 @@ pub fn handle_through_goal(
     goal_q: Query<(&EntityInstance, &GridCoords), (With<Goal>, Without<Player>)>,
     entry_q: Query<(&EntityInstance, &GridCoords), (With<Goal>, Without<Player>)>,
 ) {
-    let coords = player_q.clone();
+    let coords = **player_q;
     if level_goals.goal_locations.contains(&coords) {
         // only triggerred if the player walks on top of a goal
         for (entity, g_coords) in goal_q {
@@ -1,11 +1,13 @@
 use bevy::prelude::*;
 
+mod beds;
 mod goals;
 mod herbs;
 mod npcs;
 mod signs;
 mod walls;
 
+pub(crate) use beds::RestPoint;
 use bevy_ecs_ldtk::GridCoords;
 pub(crate) use herbs::LevelHerbs;
 pub(crate) use npcs::{LevelNPCs, NPCKind};
@@ -13,12 +15,15 @@ pub(crate) use walls::LevelWalls;
 
 use crate::{
     AppState,
+    dex::Dex,
+    items::Inventory,
     player::Player,
     save::Save,
-    team::Team,
+    taming::{PlayerAura, Rings},
+    team::{AwayMembers, Team, TeamMember},
     world::{
-        goals::GoalsPlugin, herbs::HerbsPlugin, npcs::NPCsPlugin, signs::SignsPlugin,
-        walls::WallsPlugin,
+        beds::BedsPlugin, goals::GoalsPlugin, herbs::HerbsPlugin, npcs::NPCsPlugin,
+        signs::SignsPlugin, walls::WallsPlugin,
     },
 };
 
@@ -29,6 +34,7 @@ pub struct WorldPlugin;
 impl Plugin for WorldPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(GridSize(16))
+            .add_plugins(BedsPlugin)
             .add_plugins(GoalsPlugin)
             .add_plugins(HerbsPlugin)
             .add_plugins(NPCsPlugin)
@@ -37,8 +43,9 @@ impl Plugin for WorldPlugin {
             .add_systems(OnEnter(AppState::ResumeGame), load_game)
             .add_systems(
                 OnTransition {
-                    // `init_team` loads an empty team, so it shall only be called when starting a new game.
-                    exited: AppState::MainMenu,
+                    // `init_team` loads a new team, so it shall only be called when starting a new game.
+                    // The aura of the player is chosen just before, in `AppState::NewGame`.
+                    exited: AppState::NewGame,
                     entered: AppState::InGame,
                 },
                 init_team,
@@ -62,10 +69,25 @@ impl Plugin for WorldPlugin {
     }
 }
 
-/// Load the save as a Resource.
-fn load_game(mut commands: Commands, mut next_state: ResMut<NextState<AppState>>) {
-    let save = Save::load().unwrap();
+/// Load the save as a Resource. A save that cannot be read sends the player back to the menu.
+fn load_game(mut commands: Commands, mut next_state: ResMut<NextState<AppState>>, dex: Res<Dex>) {
+    let mut save = match Save::load() {
+        Ok(save) => save,
+        Err(error) => {
+            eprintln!("{error}");
+            next_state.set(AppState::MainMenu);
+            return;
+        }
+    };
+    save.team.migrate(&mut save.away, &dex);
     commands.insert_resource(save.team.clone());
+    commands.insert_resource(save.away.clone());
+    commands.insert_resource(save.rings.clone());
+    commands.insert_resource(PlayerAura(save.aura));
+    commands.insert_resource(save.inventory.clone());
+    if let Some(rest_point) = save.rest_point.clone() {
+        commands.insert_resource(rest_point);
+    }
     commands.insert_resource(save);
     next_state.set(AppState::InGame);
 }
@@ -86,11 +108,17 @@ fn apply_save(
     }
 }
 
-fn init_team(mut commands: Commands) {
-    commands.insert_resource(Team::new());
+/// A new game starts with a first creature sharing the aura of the player: wild creatures
+/// leave an empty team alone, so it could never tame one.
+fn init_team(mut commands: Commands, aura: Res<PlayerAura>, dex: Res<Dex>) {
+    let starter = dex.random_of_aura(aura.0);
+    commands.insert_resource(Team(vec![TeamMember::new(&starter, &dex)]));
+    commands.insert_resource(AwayMembers::default());
+    commands.insert_resource(Rings::default());
+    commands.insert_resource(Inventory::default());
 }
 
-/// Despawn the world and its camera.
+/// Despawn the world and its camera, and forget where the player rests.
 fn clean_up_world(
     mut commands: Commands,
     world_q: Single<Entity, With<crate::camera::WorldBundle>>,
@@ -98,6 +126,7 @@ fn clean_up_world(
 ) {
     commands.entity(*world_q).despawn();
     commands.entity(*cam_q).despawn();
+    commands.remove_resource::<RestPoint>();
 }
 
 /// Move everything accordingly to the player's movement.
@@ -1,3 +1,5 @@
+use std::fmt;
+
 use bevy::{platform::collections::HashMap, prelude::*};
 use bevy_ecs_ldtk::{EntityInstance, GridCoords, LdtkEntity};
 
@@ -5,6 +7,7 @@ use bevy_ecs_ldtk::{EntityInstance, GridCoords, LdtkEntity};
 // ca7c1690-5e50-11f0-85ca-e96bd84a6222
 
 #[derive(Default, Component, Debug, Hash, PartialEq, Eq, Clone)]
+#[allow(clippy::upper_case_acronyms)]
 pub struct NPC;
 
 #[derive(Default, Bundle, LdtkEntity)]
@@ -48,3 +51,13 @@ impl From<String> for NPCKind {
         }
     }
 }
+
+impl fmt::Display for NPCKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Librarian => "Librarian",
+            Self::Monk => "Monk",
+            Self::Writer => "Writer",
+        })
+    }
+}
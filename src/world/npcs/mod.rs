@@ -1,4 +1,7 @@
-use bevy::app::{Plugin, Update};
+use bevy::{
+    app::{Plugin, Update},
+    prelude::{IntoScheduleConfigs, resource_exists},
+};
 use bevy_ecs_ldtk::app::LdtkEntityAppExt;
 
 mod components;
@@ -6,7 +9,7 @@ mod systems;
 
 use components::NPCsBundle;
 
-use crate::world::npcs::systems::*;
+use crate::{dex::Dex, world::npcs::systems::*};
 pub(crate) use components::{LevelNPCs, NPCKind};
 
 pub struct NPCsPlugin;
@@ -21,7 +24,7 @@ impl Plugin for NPCsPlugin {
                 Update,
                 (
                     cache_npc_locations,
-                    handle_player_interaction_with_npc,
+                    handle_player_interaction_with_npc.run_if(resource_exists::<Dex>),
                     add_sprite_to_npc,
                 ),
             );
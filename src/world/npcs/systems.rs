@@ -2,20 +2,29 @@ use bevy::prelude::*;
 use bevy_ecs_ldtk::{EntityInstance, GridCoords};
 
 use crate::{
+    dex::Dex,
+    event::TrainerEncounterEvent,
+    fight::Trainer,
     player::Player,
+    team::{Team, TeamMember},
     utils::{
-        Direction, read_dir_from_ldtk_entity, read_npc_kind_from_ldtk_entity,
-        read_str_from_ldtk_entity,
+        Direction, read_ai_from_ldtk_fields, read_dir_from_ldtk_entity,
+        read_npc_kind_from_ldtk_entity, read_str_from_ldtk_entity, read_team_from_ldtk_entity,
     },
     world::npcs::components::{LevelNPCs, NPC, NPCKind},
 };
 
 /// Handle for players interacting with NPC
 /// Might need some refactoring around reading the json values
+/// NPCs with a team challenge the player to a fight after talking. Their creatures without
+/// a level get the one of the strongest creature of the player.
 pub fn handle_player_interaction_with_npc(
     keyboard_input: Res<ButtonInput<KeyCode>>,
     player_q: Query<(&GridCoords, &Direction), With<Player>>,
     npc_q: Query<(&GridCoords, &EntityInstance), With<NPC>>,
+    mut trainer_encounter_writer: EventWriter<TrainerEncounterEvent>,
+    team: Res<Team>,
+    dex: Res<Dex>,
 ) {
     if keyboard_input.just_pressed(KeyCode::Enter) {
         let (player_grid_coords, direction) = player_q.single().unwrap();
@@ -25,6 +34,20 @@ pub fn handle_player_interaction_with_npc(
                 // Access custom fields by name
                 let chat = read_str_from_ldtk_entity("chat", npc);
                 println!("NPC says: {}", chat);
+                let player_level = team.0.iter().map(|member| member.level).max().unwrap_or(1);
+                let trainer_team: Vec<TeamMember> = read_team_from_ldtk_entity(npc, &dex)
+                    .into_iter()
+                    .map(|(creature, level)| {
+                        TeamMember::at_level(creature, level.unwrap_or(player_level), &dex)
+                    })
+                    .collect();
+                if !trainer_team.is_empty() {
+                    trainer_encounter_writer.write(TrainerEncounterEvent(Trainer {
+                        name: read_npc_kind_from_ldtk_entity(npc).to_string(),
+                        team: trainer_team,
+                        ai: read_ai_from_ldtk_fields("ai", &npc.field_instances),
+                    }));
+                }
             }
         }
     }